@@ -1,16 +1,62 @@
+use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::config::AppConfig;
+use futures_util::future::join_all;
+use tokio::sync::Semaphore;
+
+use crate::config::{AppConfig, TokenLimitPolicy};
 use crate::error::AppError;
-use crate::git::GitOperations;
-use crate::llm::{LlmProvider, LlmRequest};
-use crate::prompt::UserInteraction;
+use crate::git::{DiffResult, DiffScope, FileDiff, GitOperations, limit};
+use crate::llm::{LlmProvider, LlmRequest, LlmResponse};
+use crate::prompt::{RewordChoice, UserInteraction};
+use crate::tokenizer::Tokenizer;
+
+/// 模型名称与对应的 LlmProvider。
+pub type NamedProvider = (String, Box<dyn LlmProvider>);
+
+/// 单次运行的选项，来自命令行参数。
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// 提交前暂存所有已跟踪文件的修改
+    pub all: bool,
+    /// 包含未跟踪文件
+    pub include_untracked: bool,
+    /// 只为未暂存的变更生成 commit message，不提交
+    pub preview: bool,
+    /// 重新生成 HEAD 的 commit message 并修改 HEAD
+    pub amend: bool,
+    /// 跳过 pre-commit 和 commit-msg 钩子
+    pub no_verify: bool,
+    /// 非交互模式：生成的 commit message 写入该文件开头，不提交（供 prepare-commit-msg 钩子使用）
+    pub message_file: Option<PathBuf>,
+}
+
+impl RunOptions {
+    fn scope(&self) -> DiffScope {
+        let include_untracked = self.include_untracked;
+        if self.amend {
+            DiffScope::Amend
+        } else if self.preview {
+            DiffScope::Unstaged { include_untracked }
+        } else if self.all || include_untracked {
+            DiffScope::All { include_untracked }
+        } else {
+            DiffScope::Staged
+        }
+    }
+}
 
 /// 应用门面，持有所有 Service 的 trait object，编排主流程。
 pub struct App {
     config: AppConfig,
     git: Box<dyn GitOperations>,
-    llm: Box<dyn LlmProvider>,
+    /// 按优先级排列的模型回退链，前一个失败时依次尝试后一个
+    llms: Vec<NamedProvider>,
+    /// 首选模型对应的分词器，用于 token 限制检查
+    tokenizer: Box<dyn Tokenizer>,
     ui: Box<dyn UserInteraction>,
 }
 
@@ -18,76 +64,445 @@ impl App {
     pub fn new(
         config: AppConfig,
         git: Box<dyn GitOperations>,
-        llm: Box<dyn LlmProvider>,
+        llms: Vec<NamedProvider>,
+        tokenizer: Box<dyn Tokenizer>,
         ui: Box<dyn UserInteraction>,
     ) -> Self {
         Self {
             config,
             git,
-            llm,
+            llms,
+            tokenizer,
             ui,
         }
     }
 
-    /// 主流程入口：检测仓库 → 获取 diff → 调用 LLM → 用户确认 → 提交
-    pub async fn run(&self) -> Result<(), AppError> {
+    /// 主流程入口：检测仓库 → 获取 diff → 调用 LLM → 确认破坏性变更 → 用户确认 → 提交
+    pub async fn run(&self, options: &RunOptions) -> Result<(), AppError> {
         let current_dir = env::current_dir()?;
 
         // 1. 检测 Git 仓库
         let repo_path = self.git.discover_repo(&current_dir)?;
         println!("检测到 Git 仓库: {}", repo_path.display());
 
-        // 2. 获取暂存区 diff
-        let diff = self.git.staged_diff(&repo_path)?;
+        // --all 与 git commit -a 一致，先把工作区的变更暂存到临时 index，
+        // pre-commit、diff 和提交都使用它；取消或失败时临时 index 被删除，暂存区保持不变
+        let scope = options.scope();
+        let index = match scope {
+            DiffScope::All { include_untracked } => {
+                Some(self.git.stage_all(&repo_path, include_untracked)?)
+            }
+            _ => None,
+        };
+
+        // 提交前先执行 pre-commit：失败时不必调用 LLM，钩子重新暂存的文件也能体现在 diff 中。
+        // 预览不提交，作为钩子运行时 git 已经执行过 pre-commit
+        if !options.no_verify && !options.preview && options.message_file.is_none() {
+            self.git.run_pre_commit(&repo_path, index.as_ref())?;
+        }
+
+        // 2. 获取 diff：默认为暂存区，--all 为全部已跟踪文件，--preview 为未暂存的变更
+        let mut diff = self.git.diff(&repo_path, scope, index.as_ref())?;
         if diff.raw.is_empty() {
             return Err(AppError::Git(
-                "暂存区没有变更，请先使用 git add 添加变更".into(),
+                match scope {
+                    DiffScope::Staged => {
+                        "暂存区没有变更，请先使用 git add 添加变更，或使用 --all 提交已跟踪文件的修改"
+                    }
+                    DiffScope::All { .. } => "工作区没有变更",
+                    DiffScope::Amend => "HEAD 与暂存区相对其父提交没有变更",
+                    DiffScope::Unstaged { .. } => "工作区没有未暂存的变更",
+                }
+                .into(),
             ));
         }
+        diff.estimated_tokens = self.tokenizer.count_tokens(&diff.raw);
+        let label = match scope {
+            DiffScope::Staged => "暂存区变更",
+            DiffScope::All { .. } => "工作区变更",
+            DiffScope::Unstaged { .. } => "未暂存变更",
+            DiffScope::Amend => "HEAD 及暂存区变更",
+        };
         println!(
-            "暂存区变更: {} 个文件, 预估 {} tokens",
-            diff.files_changed, diff.estimated_tokens
+            "{}: {} 个文件, 预估 {} tokens",
+            label, diff.files_changed, diff.estimated_tokens
         );
 
-        // 3. 检查 token 限制
-        if diff.estimated_tokens > self.config.token_limit {
-            self.ui.warn(&format!(
-                "diff 预估 {} tokens，超过限制 {} tokens，可能导致截断",
-                diff.estimated_tokens, self.config.token_limit
-            ));
+        // 3. 检查 token 限制，超限时按策略缩减 diff
+        let (prompt_diff, breaking) = self.build_prompt_diff(&diff).await?;
+        let mut user_prompt = self.config.prompt.user.replace("{diff}", &prompt_diff);
+        // 修改 HEAD 时把原有的 message 作为草稿交给模型
+        if scope == DiffScope::Amend {
+            let draft = self.git.head_message(&repo_path)?;
+            append_draft(
+                &mut user_prompt,
+                "The commit being amended currently has the following message. \
+Use it as a draft and keep whatever is still accurate:",
+                &draft,
+            );
         }
 
         // 4. 构建 LLM 请求并调用
         println!("正在调用 LLM 生成 commit message...");
-        let user_prompt = self.config.prompt.user.replace("{diff}", &diff.raw);
         let request = LlmRequest::builder()
             .system_prompt(&self.config.prompt.system)
             .user_prompt(&user_prompt)
-            .diff_content(&diff.raw)
+            .diff_content(&prompt_diff)
             .build()?;
 
-        let response = self.llm.generate(&request).await?;
+        let interactive = options.message_file.is_none();
+        let (model_name, response) = self.generate(&request, interactive).await?;
+        println!("使用模型: {}", model_name);
 
         if let Some(tokens) = response.usage_tokens {
             println!("LLM 消耗 tokens: {}", tokens);
         }
 
-        // 5. 用户确认
-        let confirmed = self.ui.confirm_commit(&response.commit_message)?;
+        // 预览模式只展示生成结果
+        if options.preview {
+            println!("\n{}\n", response.commit_message);
+            println!("预览模式，未提交。");
+            return Ok(());
+        }
+
+        // 作为钩子运行时无法交互：检测到的破坏性变更以注释行列在 message 之后，
+        // 由用户在编辑器中决定是否保留脚注
+        let mut message = response.commit_message;
+        if let Some(path) = &options.message_file {
+            if !breaking.is_empty() && !has_breaking_footer(&message) {
+                message = format!("{}\n\n{}", message.trim_end(), breaking_comment(&breaking));
+            }
+            prepend_to_file(path, &message)?;
+            println!("已写入 {}", path.display());
+            return Ok(());
+        }
+
+        // 5. 检测到破坏性变更而模型没有写脚注时，由用户确认是否补上
+        if !breaking.is_empty()
+            && !has_breaking_footer(&message)
+            && self.ui.confirm_breaking_change(&breaking)?
+        {
+            message = append_breaking_footer(&message, &breaking);
+        }
+
+        // 6. 用户确认
+        let confirmed = self.ui.confirm_commit(&message)?;
         if !confirmed {
             println!("已取消提交。");
             return Ok(());
         }
 
-        // 6. 提交
-        self.git.commit(&repo_path, &response.commit_message)?;
+        // 7. 提交，--all 时提交临时 index，--amend 时重写 HEAD
+        if scope == DiffScope::Amend {
+            self.git.amend(&repo_path, &message, options.no_verify)?;
+            println!("已修改 HEAD 提交！");
+            return Ok(());
+        }
+        self.git
+            .commit(&repo_path, &message, options.no_verify, index)?;
         println!("提交成功！");
 
         Ok(())
     }
 
-    /// 列出所有可用模型
-    pub fn list_models(&self) {
+    /// 为提交范围内的每个提交重新生成 message，逐条由用户接受、编辑或跳过，
+    /// 然后以新的 message 重放这些提交。工作区不干净时拒绝执行
+    pub async fn reword(&self, range: &str) -> Result<(), AppError> {
+        let current_dir = env::current_dir()?;
+        let repo_path = self.git.discover_repo(&current_dir)?;
+        if !self.git.is_clean(&repo_path)? {
+            return Err(AppError::Git(
+                "工作区有未提交的修改，请先提交或 stash 后再重写历史".into(),
+            ));
+        }
+
+        let commits = self.git.commits_in_range(&repo_path, range)?;
+        if commits.is_empty() {
+            return Err(AppError::Git(format!("范围 {} 内没有提交", range)));
+        }
+
+        // 先备份原 HEAD，备份失败时不必再调用 LLM
+        let backup = self.git.backup_head(&repo_path)?;
+
+        // 1. 依次为每个提交生成新的 message
+        let total = commits.len();
+        let mut proposals = Vec::with_capacity(total);
+        for (i, commit) in commits.iter().enumerate() {
+            let mut diff = self.git.commit_diff(&repo_path, &commit.id)?;
+            diff.estimated_tokens = self.tokenizer.count_tokens(&diff.raw);
+            let (prompt_diff, _) = self.build_prompt_diff(&diff).await?;
+            let mut user_prompt = self.config.prompt.user.replace("{diff}", &prompt_diff);
+            append_draft(
+                &mut user_prompt,
+                "The commit currently has the following message, which may be uninformative:",
+                &commit.message,
+            );
+            let request = LlmRequest::builder()
+                .system_prompt(&self.config.prompt.system)
+                .user_prompt(&user_prompt)
+                .diff_content(&prompt_diff)
+                .build()?;
+            let (_, response) = self.generate(&request, false).await?;
+            proposals.push(response.commit_message);
+            self.ui
+                .display_progress("正在生成 commit message", i + 1, total);
+        }
+
+        // 2. 逐条审阅
+        let mut messages = HashMap::new();
+        for (i, (commit, proposed)) in commits.iter().zip(&proposals).enumerate() {
+            let choice = self.ui.review_reword(
+                &commit.short_id,
+                (i + 1, total),
+                &commit.message,
+                proposed,
+            )?;
+            match choice {
+                RewordChoice::Accept => {
+                    messages.insert(commit.id.clone(), proposed.clone());
+                }
+                RewordChoice::Edit(message) => {
+                    messages.insert(commit.id.clone(), message);
+                }
+                RewordChoice::Skip => {}
+            }
+        }
+        if messages.is_empty() {
+            self.git.delete_backup(&repo_path, &backup)?;
+            println!("没有需要重写的提交。");
+            return Ok(());
+        }
+
+        // 3. 确认后重放提交
+        if !self.ui.confirm_reword(messages.len())? {
+            self.git.delete_backup(&repo_path, &backup)?;
+            println!("已取消重写。");
+            return Ok(());
+        }
+        self.git.rewrite_messages(&repo_path, &commits, &messages)?;
+        println!("已重写 {} 个提交的 message。", messages.len());
+        println!(
+            "原 HEAD 已备份到 {}，如需恢复: git reset --hard {}",
+            backup, backup
+        );
+        Ok(())
+    }
+
+    /// 按 token 限制缩减 diff，并附加检测到的破坏性变更，
+    /// 返回放入 prompt 的 diff 与破坏性变更列表
+    async fn build_prompt_diff(
+        &self,
+        diff: &DiffResult,
+    ) -> Result<(String, Vec<String>), AppError> {
+        let mut prompt_diff = self.prepare_diff(diff).await?;
+        let breaking = diff.breaking_changes();
+        if !breaking.is_empty() {
+            prompt_diff.push_str(
+                "\nProbable breaking changes to the public API, detected by comparing the old and new versions of the changed files:\n",
+            );
+            for change in &breaking {
+                prompt_diff.push_str(&format!("- {}\n", change));
+            }
+        }
+        Ok((prompt_diff, breaking))
+    }
+
+    /// 检查完整 prompt（system prompt + user prompt 模板 + diff）的 token 数，
+    /// 超过 token_limit 时按 on_token_limit 策略处理，返回最终放入 prompt 的 diff
+    async fn prepare_diff(&self, diff: &DiffResult) -> Result<String, AppError> {
+        let tokenizer = self.tokenizer.as_ref();
+        let fixed_tokens = tokenizer.count_tokens(&self.config.prompt.system)
+            + tokenizer.count_tokens(&self.config.prompt.user.replace("{diff}", ""));
+        let prompt_tokens = fixed_tokens + diff.estimated_tokens;
+        let limit = self.config.token_limit;
+        if prompt_tokens <= limit {
+            let mut text = diff.raw.clone();
+            if self.config.diff.full_functions {
+                let budget = limit - prompt_tokens;
+                text.push_str(&limit::attach_functions(&diff.files, budget, tokenizer));
+            }
+            return Ok(text);
+        }
+
+        let budget = limit.saturating_sub(fixed_tokens);
+        let reduced = match self.config.on_token_limit {
+            TokenLimitPolicy::Warn => {
+                self.ui.warn(&format!(
+                    "prompt 预估 {} tokens（其中 diff {} tokens），超过限制 {} tokens，可能导致截断",
+                    prompt_tokens, diff.estimated_tokens, limit
+                ));
+                return Ok(diff.raw.clone());
+            }
+            TokenLimitPolicy::Abort => {
+                return Err(AppError::TokenLimitExceeded {
+                    current: prompt_tokens,
+                    limit,
+                });
+            }
+            TokenLimitPolicy::MapReduce => return self.map_reduce(&diff.files).await,
+            TokenLimitPolicy::Truncate => limit::truncate(&diff.files, budget, tokenizer),
+            TokenLimitPolicy::Compact => limit::compact(&diff.files, budget, tokenizer),
+            TokenLimitPolicy::Summarize => limit::summarize(&diff.files, budget, tokenizer),
+        };
+
+        let text = reduced.render();
+        self.ui.warn(&format!(
+            "prompt 预估 {} tokens，超过限制 {} tokens，已按 {:?} 策略将 diff 缩减为 {} tokens，省略或截断 {} 个文件",
+            prompt_tokens,
+            limit,
+            self.config.on_token_limit,
+            tokenizer.count_tokens(&text),
+            reduced.elided.len()
+        ));
+        Ok(text)
+    }
+
+    /// map_reduce 策略：将 diff 切分为多个片段，并发请求 LLM 为每个片段生成摘要，
+    /// 返回拼接后的摘要，作为最终生成 commit message 的输入
+    async fn map_reduce(&self, files: &[FileDiff]) -> Result<String, AppError> {
+        let summary_prompt = &self.config.prompt.summary;
+        let budget = self
+            .config
+            .token_limit
+            .saturating_sub(self.tokenizer.count_tokens(summary_prompt));
+        let chunks = limit::chunk(files, budget, self.tokenizer.as_ref());
+        let total = chunks.len();
+        self.ui.warn(&format!(
+            "diff 超过 token 限制，拆分为 {} 个片段分别摘要",
+            total
+        ));
+
+        let semaphore = Semaphore::new(self.config.map_concurrency.max(1));
+        let done = AtomicUsize::new(0);
+        let tasks = chunks.iter().map(|chunk| {
+            let semaphore = &semaphore;
+            let done = &done;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .map_err(|e| AppError::Llm(format!("无法获取并发许可: {}", e)))?;
+                let request = LlmRequest::builder()
+                    .system_prompt(summary_prompt)
+                    .user_prompt(chunk)
+                    .diff_content(chunk)
+                    .build()?;
+                let (_, response) = self.generate(&request, false).await?;
+                let finished = done.fetch_add(1, Ordering::SeqCst) + 1;
+                self.ui
+                    .display_progress("正在摘要 diff 片段", finished, total);
+                Ok::<_, AppError>(response.commit_message)
+            }
+        });
+        let summaries = join_all(tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut text = String::from(
+            "The staged diff was too large to include directly. \
+Summaries of each part of the diff:\n\n",
+        );
+        for (i, summary) in summaries.iter().enumerate() {
+            text.push_str(&format!("Part {}/{}:\n{}\n\n", i + 1, total, summary));
+        }
+        Ok(text)
+    }
+
+    /// 按回退链依次调用模型，返回实际生成结果的模型名称和响应。
+    /// stream 为 true 时实时展示流式输出
+    async fn generate(
+        &self,
+        request: &LlmRequest,
+        stream: bool,
+    ) -> Result<(&str, LlmResponse), AppError> {
+        let mut last_err = None;
+        for (i, (name, llm)) in self.llms.iter().enumerate() {
+            let result = if stream {
+                // 流式输出时边生成边展示；不支持流式的后端直接返回完整响应
+                let mut streamed = false;
+                let ui = &self.ui;
+                let mut on_delta = |delta: &str| {
+                    streamed = true;
+                    ui.display_stream_delta(delta);
+                };
+                let result = llm.generate_stream(request, &mut on_delta).await;
+                if streamed {
+                    println!();
+                }
+                result
+            } else {
+                llm.generate(request).await
+            };
+
+            match result {
+                Ok(response) => return Ok((name, response)),
+                Err(e) if !e.allows_fallback() => return Err(e),
+                Err(e) => {
+                    if let Some((next, _)) = self.llms.get(i + 1) {
+                        self.ui
+                            .warn(&format!("模型 {} 调用失败: {}，改用 {}", name, e, next));
+                    }
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| AppError::Llm("没有可用的模型".into())))
+    }
+
+    /// 列出所有可用模型，以及各后端实际安装的模型（如 Ollama）
+    pub async fn list_models(&self) -> Result<(), AppError> {
         self.ui.display_model_list(&self.config.models);
+
+        for (_, llm) in &self.llms {
+            match llm.installed_models().await {
+                Ok(installed) if !installed.is_empty() => {
+                    self.ui.display_installed_models(llm.name(), &installed);
+                }
+                Ok(_) => {}
+                Err(e) => self.ui.warn(&format!("无法获取已安装的模型: {}", e)),
+            }
+        }
+        Ok(())
     }
 }
+
+/// 在 user prompt 末尾附上现有的 commit message 作为参考
+fn append_draft(user_prompt: &mut String, note: &str, draft: &str) {
+    user_prompt.push_str(&format!("\n\n{}\n{}", note, draft.trim_end()));
+}
+
+/// 把 message 写到文件开头，保留文件原有的内容（git 的注释、git commit -v 的 diff 等）
+fn prepend_to_file(path: &Path, message: &str) -> Result<(), AppError> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let existing = existing.trim_start_matches('\n');
+    fs::write(path, format!("{}\n\n{}", message.trim_end(), existing))?;
+    Ok(())
+}
+
+/// 提交信息中是否已有 Conventional Commits 的破坏性变更脚注
+fn has_breaking_footer(message: &str) -> bool {
+    message
+        .lines()
+        .any(|l| l.starts_with("BREAKING CHANGE:") || l.starts_with("BREAKING-CHANGE:"))
+}
+
+/// 列出破坏性变更并给出被注释掉的 `BREAKING CHANGE:` 脚注，git 提交时会去掉这些注释行
+fn breaking_comment(changes: &[String]) -> String {
+    let mut comment = String::from("# llmc 检测到可能破坏兼容性的公开 API 变化：\n");
+    for change in changes {
+        comment.push_str(&format!("#   - {}\n", change));
+    }
+    comment.push_str("# 确认是破坏性变更时，删除下一行开头的 \"# \" 保留脚注：\n");
+    comment.push_str(&format!("# BREAKING CHANGE: {}\n", changes.join("; ")));
+    comment
+}
+
+/// 在提交信息末尾追加 `BREAKING CHANGE:` 脚注
+fn append_breaking_footer(message: &str, changes: &[String]) -> String {
+    format!(
+        "{}\n\nBREAKING CHANGE: {}",
+        message.trim_end(),
+        changes.join("; ")
+    )
+}
@@ -1,10 +1,13 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
 /// LLM-powered Git Commit assistant
 #[derive(Parser)]
 #[command(name = "llmc", about = "LLM-powered Git Commit assistant")]
 pub struct CliArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// 切换使用的模型
     #[arg(long)]
     pub model: Option<String>,
@@ -20,4 +23,61 @@ pub struct CliArgs {
     /// 指定配置文件路径
     #[arg(long)]
     pub config: Option<PathBuf>,
+
+    /// 提交所有已跟踪文件的修改和删除，同 git commit -a
+    #[arg(short = 'a', long)]
+    pub all: bool,
+
+    /// 同时包含未跟踪的新文件（可与 --all 或 --preview 一起使用，单独使用时等同于 --all）
+    #[arg(long)]
+    pub include_untracked: bool,
+
+    /// 为尚未暂存的工作区变更生成 commit message，只展示不提交
+    #[arg(long, conflicts_with = "all")]
+    pub preview: bool,
+
+    /// 重新生成 HEAD 的 commit message，并连同新暂存的变更修改 HEAD，同 git commit --amend
+    #[arg(long, conflicts_with_all = ["all", "include_untracked", "preview"])]
+    pub amend: bool,
+
+    /// 跳过 pre-commit 和 commit-msg 钩子，同 git commit --no-verify
+    #[arg(short = 'n', long, conflicts_with = "preview")]
+    pub no_verify: bool,
+
+    /// 非交互模式：把生成的 commit message 写入文件开头而不提交，供 prepare-commit-msg 钩子调用
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["all", "include_untracked", "preview", "amend", "no_verify"]
+    )]
+    pub message_file: Option<PathBuf>,
+}
+
+/// 子命令。
+#[derive(Subcommand)]
+pub enum Command {
+    /// 为提交范围内的每个提交重新生成 message，逐条确认后重写历史
+    Reword {
+        /// 提交范围，如 main..HEAD；只给出 <base> 时等同于 <base>..HEAD
+        range: String,
+    },
+
+    /// 管理调用 llmc 生成 commit message 的 prepare-commit-msg 钩子
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+}
+
+/// hook 子命令的操作。
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// 在当前仓库安装钩子，之后 git commit 打开编辑器时会预先填好生成的 message
+    Install {
+        /// 覆盖已存在的 prepare-commit-msg 钩子
+        #[arg(long)]
+        force: bool,
+    },
+    /// 删除 llmc 安装的钩子
+    Uninstall,
 }
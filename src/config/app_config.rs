@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use super::DiffConfig;
 use super::ModelConfig;
 use super::PromptConfig;
 use crate::error::AppError;
@@ -11,12 +12,62 @@ const DEFAULT_CONFIG: &str = include_str!("../../config.toml");
 /// 全局配置，从 config.toml 反序列化。
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AppConfig {
-    pub default_model: String,
+    /// 默认模型，可以是单个名称或按优先级排列的回退列表
+    pub default_model: ModelChain,
     pub token_limit: usize,
+    /// prompt 超过 token_limit 时的处理策略
+    #[serde(default)]
+    pub on_token_limit: TokenLimitPolicy,
+    /// map_reduce 策略同时进行的摘要请求数
+    #[serde(default = "default_map_concurrency")]
+    pub map_concurrency: usize,
     pub prompt: PromptConfig,
+    /// diff 生成选项
+    #[serde(default)]
+    pub diff: DiffConfig,
     pub models: Vec<ModelConfig>,
 }
 
+/// 模型名称列表，兼容 `"deepseek"` 与 `["deepseek", "local-qwen"]` 两种写法。
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum ModelChain {
+    Single(String),
+    List(Vec<String>),
+}
+
+/// prompt 超过 token 限制时的处理策略。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenLimitPolicy {
+    /// 只给出警告，仍然发送完整 diff
+    #[default]
+    Warn,
+    /// 返回 TokenLimitExceeded 错误
+    Abort,
+    /// 按文件在 hunk 边界处截断
+    Truncate,
+    /// 先省略锁文件、生成文件和上下文行，仍超限时再截断
+    Compact,
+    /// 只发送每个文件的变更统计和 hunk 头
+    Summarize,
+    /// 将 diff 切分为多个片段分别请求 LLM 摘要，再根据摘要生成 commit message
+    MapReduce,
+}
+
+fn default_map_concurrency() -> usize {
+    4
+}
+
+impl ModelChain {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            ModelChain::Single(name) => vec![name.as_str()],
+            ModelChain::List(names) => names.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
 impl AppConfig {
     /// 从指定路径加载配置文件
     pub fn load(path: &Path) -> Result<Self, AppError> {
@@ -97,4 +148,32 @@ impl AppConfig {
             .find(|m| m.name == name)
             .ok_or_else(|| AppError::ModelNotFound(name.to_string()))
     }
+
+    /// 解析按优先级排列的模型回退链。
+    /// 起点为用户指定的模型，未指定时使用 default_model；
+    /// 随后沿每个模型的 fallback 字段展开，重复的模型只保留第一次出现。
+    pub fn model_chain(&self, selected: Option<&str>) -> Result<Vec<&ModelConfig>, AppError> {
+        let roots = match selected {
+            Some(name) => vec![name],
+            None => self.default_model.names(),
+        };
+
+        let mut chain: Vec<&ModelConfig> = Vec::new();
+        for root in roots {
+            let mut next = Some(root);
+            while let Some(name) = next {
+                if chain.iter().any(|m| m.name == name) {
+                    break;
+                }
+                let model = self.find_model(name)?;
+                chain.push(model);
+                next = model.fallback.as_deref();
+            }
+        }
+
+        if chain.is_empty() {
+            return Err(AppError::Config("default_model 不能为空".into()));
+        }
+        Ok(chain)
+    }
 }
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// 生成暂存区 diff 时的选项。
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct DiffConfig {
+    /// 重命名检测的相似度阈值（0-100），与 git 的 `-M` 一致
+    pub rename_threshold: u16,
+    /// 是否检测复制（从本次修改过的文件中查找来源），与 git 的 `-C` 一致
+    pub detect_copies: bool,
+    /// 从 prompt 中排除的文件（gitignore 语法），与仓库的 .llmcignore 合并生效。
+    /// 被排除的文件只保留变更行数统计
+    pub exclude: Vec<String>,
+    /// 是否将 JSON/TOML/YAML 文件的行 diff 替换为键路径级别的变更列表
+    pub semantic: bool,
+    /// 是否用语法树标注每个 hunk 所在的函数、impl 或类，并列出被修改的符号
+    /// （支持 Rust、Python、TypeScript/JavaScript、Go）
+    pub symbols: bool,
+    /// 在 token 预算允许时附加被修改函数的完整源码，需要开启 symbols
+    pub full_functions: bool,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            rename_threshold: 50,
+            detect_copies: true,
+            exclude: Vec::new(),
+            semantic: true,
+            symbols: false,
+            full_functions: false,
+        }
+    }
+}
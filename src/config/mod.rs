@@ -1,7 +1,11 @@
 mod app_config;
+mod diff_config;
 mod model_config;
 mod prompt_config;
+mod retry_config;
 
-pub use app_config::AppConfig;
-pub use model_config::ModelConfig;
+pub use app_config::{AppConfig, ModelChain, TokenLimitPolicy};
+pub use diff_config::DiffConfig;
+pub use model_config::{ModelConfig, ModelOptions};
 pub use prompt_config::PromptConfig;
+pub use retry_config::RetryConfig;
@@ -1,18 +1,63 @@
 use serde::{Deserialize, Serialize};
 
+use super::RetryConfig;
+use crate::error::AppError;
+
 /// 单个模型的配置。
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ModelConfig {
     /// 模型标识名
     pub name: String,
-    /// 后端类型，如 "openai"
+    /// 后端类型，如 "openai"、"anthropic"、"ollama"
     pub provider: String,
     /// API 地址
     pub api_base: String,
-    /// 存放 API Key 的环境变量名
-    pub api_key_env: String,
+    /// 存放 API Key 的环境变量名（本地后端如 ollama 可省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
     /// 实际模型 ID
     pub model_id: String,
     /// 可选：最大生成 token 数
     pub max_tokens: Option<usize>,
+    /// 可选：是否使用流式输出（默认开启，仅对支持流式的后端生效）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    /// 可选：传递给后端的推理参数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<ModelOptions>,
+    /// 可选：请求失败时的重试策略，缺省使用默认策略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    /// 可选：本模型调用失败时改用的模型名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    /// 可选：tiktoken 格式的 BPE 词表路径（如 cl100k_base.tiktoken），
+    /// 用于精确估算 token 数；缺省或为 "heuristic" 时使用启发式估算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+}
+
+/// 模型推理参数，对应 Ollama 的 `options` 块。
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ModelOptions {
+    /// 上下文窗口大小
+    pub num_ctx: Option<usize>,
+    /// 采样温度
+    pub temperature: Option<f32>,
+}
+
+impl ModelConfig {
+    /// 从 api_key_env 指定的环境变量读取 API Key
+    pub fn api_key(&self) -> Result<String, AppError> {
+        let env_name = self
+            .api_key_env
+            .as_deref()
+            .ok_or_else(|| AppError::Config(format!("模型 {} 未配置 api_key_env", self.name)))?;
+        std::env::var(env_name).map_err(|_| {
+            AppError::Config(format!(
+                "环境变量 {} 未设置，请设置对应的 API Key",
+                env_name
+            ))
+        })
+    }
 }
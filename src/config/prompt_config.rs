@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+/// 默认的分片摘要 system prompt，用于 map_reduce 策略
+const DEFAULT_SUMMARY_PROMPT: &str = "You summarize one part of a larger git diff. \
+Describe what changed and why in at most 5 short bullet points, naming the files and \
+functions involved. Output only the bullet points.";
+
 /// Prompt 配置，控制发送给 LLM 的 system prompt 和 user prompt 模板。
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PromptConfig {
@@ -7,4 +12,11 @@ pub struct PromptConfig {
     pub system: String,
     /// user prompt 模板，{diff} 占位符会被替换为实际 diff 内容
     pub user: String,
+    /// map_reduce 策略中为每个 diff 片段生成摘要时使用的 system prompt
+    #[serde(default = "default_summary_prompt")]
+    pub summary: String,
+}
+
+fn default_summary_prompt() -> String {
+    DEFAULT_SUMMARY_PROMPT.to_string()
 }
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// 请求失败时的重试策略，按模型配置。
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// 最大尝试次数（包含首次请求），1 表示不重试
+    pub max_attempts: u32,
+    /// 首次重试前的等待时间（毫秒），之后按指数增长
+    pub base_delay_ms: u64,
+    /// 单次等待的上限（毫秒），同样约束 Retry-After
+    pub max_delay_ms: u64,
+    /// 是否在等待时间上叠加随机抖动，避免多个客户端同时重试
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            jitter: true,
+        }
+    }
+}
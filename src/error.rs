@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 /// 统一错误类型，使用 thiserror 派生。
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -7,9 +9,37 @@ pub enum AppError {
     #[error("Config error: {0}")]
     Config(String),
 
+    /// 无法归入以下具体类别的 LLM 错误
     #[error("LLM error: {0}")]
     Llm(String),
 
+    /// API Key 无效或无权访问（401/403）
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// 触发限流（429），retry_after 来自服务端的 Retry-After 头
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+
+    /// 输入超出模型上下文窗口，max 为服务端报告的上限（如果能解析出来）
+    #[error("Context length exceeded: {message}")]
+    ContextLengthExceeded { max: Option<usize>, message: String },
+
+    /// 请求超时（包括网关超时 504）
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    /// 无法连接后端，或后端返回 5xx
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    /// 后端返回了无法解析的响应
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -19,3 +49,35 @@ pub enum AppError {
     #[error("Model not found: {0}")]
     ModelNotFound(String),
 }
+
+impl AppError {
+    /// 是否属于后端调用失败，可以改用回退链中的下一个模型
+    pub fn allows_fallback(&self) -> bool {
+        matches!(
+            self,
+            AppError::Unauthorized(_)
+                | AppError::RateLimited { .. }
+                | AppError::ContextLengthExceeded { .. }
+                | AppError::Timeout(_)
+                | AppError::ServiceUnavailable(_)
+        )
+    }
+
+    /// 进程退出码，每种错误对应一个固定值，便于脚本区分失败原因
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Llm(_) => 1,
+            AppError::Git(_) => 2,
+            AppError::Config(_) => 3,
+            AppError::Io(_) => 4,
+            AppError::TokenLimitExceeded { .. } => 5,
+            AppError::ModelNotFound(_) => 6,
+            AppError::Unauthorized(_) => 10,
+            AppError::RateLimited { .. } => 11,
+            AppError::ContextLengthExceeded { .. } => 12,
+            AppError::Timeout(_) => 13,
+            AppError::ServiceUnavailable(_) => 14,
+            AppError::InvalidResponse(_) => 15,
+        }
+    }
+}
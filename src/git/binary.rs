@@ -0,0 +1,103 @@
+/// 按文件头魔数识别的常见二进制类型
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (b"\xff\xd8\xff", "JPEG image"),
+    (b"GIF87a", "GIF image"),
+    (b"GIF89a", "GIF image"),
+    (b"%PDF-", "PDF document"),
+    (b"PK\x03\x04", "ZIP archive"),
+    (b"\x1f\x8b", "gzip archive"),
+    (b"7z\xbc\xaf\x27\x1c", "7z archive"),
+    (b"\x7fELF", "ELF executable"),
+    (b"MZ", "Windows executable"),
+    (b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+    (b"\0asm", "WebAssembly module"),
+    (b"SQLite format 3\0", "SQLite database"),
+    (b"OggS", "Ogg media"),
+    (b"ID3", "MP3 audio"),
+    (b"fLaC", "FLAC audio"),
+    (b"wOFF", "WOFF font"),
+    (b"wOF2", "WOFF2 font"),
+    (b"\0\x01\0\0", "TrueType font"),
+    (b"OTTO", "OpenType font"),
+    (b"8BPS", "Photoshop image"),
+];
+
+/// 与 git 相同的二进制判断：前 8000 字节中含有 NUL
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|&b| b == 0)
+}
+
+/// 描述二进制文件的变化：类型与大小变化，如
+/// `binary PNG image, 12.4 KiB -> 13.0 KiB (+612 B)`。
+/// old/new 为 None 表示文件不存在（新增或删除）
+pub fn summarize(path: &str, old: Option<&[u8]>, new: Option<&[u8]>) -> String {
+    let kind = new
+        .or(old)
+        .map_or_else(|| kind_by_extension(path), |c| kind(path, c));
+    match (old, new) {
+        (None, Some(new)) => format!("binary {} added, {}", kind, format_size(new.len() as u64)),
+        (Some(old), None) => {
+            format!(
+                "binary {} removed, was {}",
+                kind,
+                format_size(old.len() as u64)
+            )
+        }
+        (Some(old), Some(new)) => {
+            let old_kind = self::kind(path, old);
+            let kind = if old_kind == kind {
+                kind
+            } else {
+                format!("{} -> {}", old_kind, kind)
+            };
+            format!(
+                "binary {}, {}",
+                kind,
+                size_change(old.len() as u64, new.len() as u64)
+            )
+        }
+        (None, None) => format!("binary {}", kind),
+    }
+}
+
+/// 描述大小变化，如 `12.4 KiB -> 13.0 KiB (+612 B)`
+pub fn size_change(old: u64, new: u64) -> String {
+    let delta = if new >= old {
+        format!("+{}", format_size(new - old))
+    } else {
+        format!("-{}", format_size(old - new))
+    };
+    format!("{} -> {} ({})", format_size(old), format_size(new), delta)
+}
+
+/// 以二进制单位格式化字节数
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn kind(path: &str, content: &[u8]) -> String {
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| content.starts_with(magic))
+        .map_or_else(|| kind_by_extension(path), |(_, name)| name.to_string())
+}
+
+/// 无法按魔数识别时用扩展名描述，如 `.uasset file`
+pub fn kind_by_extension(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!(".{} file", ext.to_ascii_lowercase()),
+        _ => "file".to_string(),
+    }
+}
@@ -1,3 +1,32 @@
+use std::fmt::Write;
+
+use crate::tokenizer::{HeuristicTokenizer, Tokenizer};
+
+/// 生成 diff 的范围。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffScope {
+    /// HEAD 与暂存区，即将要提交的内容
+    Staged,
+    /// HEAD 与工作区的全部变更，相当于 `git commit -a`
+    All { include_untracked: bool },
+    /// 暂存区与工作区，即尚未暂存的变更
+    Unstaged { include_untracked: bool },
+    /// HEAD 的父提交与暂存区，即修改 HEAD 后它将包含的全部变更
+    Amend,
+}
+
+impl DiffScope {
+    /// 是否包含未跟踪文件
+    pub fn include_untracked(self) -> bool {
+        match self {
+            Self::Staged | Self::Amend => false,
+            Self::All { include_untracked } | Self::Unstaged { include_untracked } => {
+                include_untracked
+            }
+        }
+    }
+}
+
 /// 暂存区 diff 的结构化表示。
 pub struct DiffResult {
     /// 原始 diff 文本
@@ -6,16 +35,228 @@ pub struct DiffResult {
     pub files_changed: usize,
     /// 预估 token 数
     pub estimated_tokens: usize,
+    /// 按文件拆分的结构化 diff
+    pub files: Vec<FileDiff>,
 }
 
 impl DiffResult {
-    /// 创建 DiffResult，自动估算 token 数（按字符数 / 4）
-    pub fn new(raw: String, files_changed: usize) -> Self {
-        let estimated_tokens = raw.len() / 4;
+    /// 由结构化的文件列表创建 DiffResult，渲染 patch 文本并使用启发式分词器估算 token 数
+    pub fn new(files: Vec<FileDiff>) -> Self {
+        let raw = render_files(&files);
+        let estimated_tokens = HeuristicTokenizer::new().count_tokens(&raw);
         Self {
             raw,
-            files_changed,
+            files_changed: files.len(),
             estimated_tokens,
+            files,
+        }
+    }
+
+    /// 所有文件中可能破坏兼容性的变化，每项带上文件路径
+    pub fn breaking_changes(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .flat_map(|f| {
+                f.breaking_changes
+                    .iter()
+                    .map(move |c| format!("{}: {}", f.path(), c))
+            })
+            .collect()
+    }
+}
+
+/// 将多个文件渲染为 patch 文本
+pub fn render_files(files: &[FileDiff]) -> String {
+    files.iter().map(FileDiff::render).collect()
+}
+
+/// 文件的变更类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    /// 文件类型变化，如普通文件变为符号链接
+    TypeChange,
+}
+
+/// 单个文件的 diff。
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    /// 变更前的路径，新增文件为 None
+    pub old_path: Option<String>,
+    /// 变更后的路径，删除文件为 None
+    pub new_path: Option<String>,
+    pub status: FileStatus,
+    /// 变更前的文件模式（如 0o100644），新增文件为 0
+    pub old_mode: u32,
+    /// 变更后的文件模式，删除文件为 0
+    pub new_mode: u32,
+    /// 重命名或复制时的相似度（0-100）
+    pub similarity: Option<u16>,
+    /// 是否为二进制文件
+    pub binary: bool,
+    /// 替代 hunk 的文本摘要（如被排除文件的统计、锁文件的依赖变更），
+    /// 存在时不渲染 patch，只渲染 `<path>: <summary>`
+    pub summary: Option<String>,
+    /// 被修改的函数、impl、类等符号，开启 `diff.symbols` 时填充
+    pub symbols: Vec<Symbol>,
+    /// 可能破坏兼容性的公开 API 变化（目前只分析 Rust 源文件）
+    pub breaking_changes: Vec<String>,
+    /// 新增行数
+    pub insertions: usize,
+    /// 删除行数
+    pub deletions: usize,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    /// 文件的展示路径：优先取变更后的路径
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// 渲染文件头（`diff --git` 到第一个 `@@` 之前的部分）
+    pub fn render_header(&self) -> String {
+        if let Some(summary) = &self.summary {
+            return format!("{}: {}\n", self.path(), summary);
+        }
+
+        let old = self.old_path.as_deref().unwrap_or(self.path());
+        let new = self.new_path.as_deref().unwrap_or(self.path());
+
+        let mut out = format!("diff --git a/{} b/{}\n", old, new);
+        match self.status {
+            FileStatus::Added => {
+                let _ = writeln!(out, "new file mode {:o}", self.new_mode);
+            }
+            FileStatus::Deleted => {
+                let _ = writeln!(out, "deleted file mode {:o}", self.old_mode);
+            }
+            _ if self.old_mode != self.new_mode => {
+                let _ = writeln!(
+                    out,
+                    "old mode {:o}\nnew mode {:o}",
+                    self.old_mode, self.new_mode
+                );
+            }
+            _ => {}
+        }
+        if matches!(self.status, FileStatus::Renamed | FileStatus::Copied) {
+            let verb = if self.status == FileStatus::Renamed {
+                "rename"
+            } else {
+                "copy"
+            };
+            if let Some(similarity) = self.similarity {
+                let _ = writeln!(out, "similarity index {}%", similarity);
+            }
+            let _ = writeln!(out, "{verb} from {old}\n{verb} to {new}");
+        }
+        let old_side = self
+            .old_path
+            .as_ref()
+            .map_or("/dev/null".to_string(), |p| format!("a/{}", p));
+        let new_side = self
+            .new_path
+            .as_ref()
+            .map_or("/dev/null".to_string(), |p| format!("b/{}", p));
+        if !self.symbols.is_empty() {
+            let names: Vec<&str> = self.symbols.iter().map(|s| s.name.as_str()).collect();
+            let _ = writeln!(out, "changed symbols: {}", names.join(", "));
+        }
+        if self.binary {
+            let _ = writeln!(out, "Binary files {} and {} differ", old_side, new_side);
+        } else if !self.hunks.is_empty() {
+            let _ = writeln!(out, "--- {}\n+++ {}", old_side, new_side);
+        }
+        out
+    }
+
+    /// 用文本摘要替代 hunk
+    pub fn replace_with_summary(&mut self, summary: String) {
+        self.summary = Some(summary);
+        self.hunks.clear();
+    }
+
+    /// 渲染完整的 patch 文本
+    pub fn render(&self) -> String {
+        let mut out = self.render_header();
+        for hunk in &self.hunks {
+            out.push_str(&hunk.render());
+        }
+        out
+    }
+}
+
+/// diff 涉及的符号（函数、impl、类等）。
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    /// 由外到内的符号路径，如 `impl Parser > fn parse_header`
+    pub name: String,
+    /// 最内层函数的完整源码，符号不在函数内时为 None
+    pub source: Option<String>,
+}
+
+/// diff 中的一个 hunk。
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    /// `@@ -a,b +c,d @@ context` 头部行（不含换行）
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+impl Hunk {
+    /// 渲染 hunk 文本（包含头部行）
+    pub fn render(&self) -> String {
+        let mut out = format!("{}\n", self.header);
+        for line in &self.lines {
+            line.render_into(&mut out);
         }
+        out
+    }
+}
+
+/// diff 行的类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+    /// `\ No newline at end of file` 标记
+    NoNewlineAtEof,
+}
+
+/// hunk 中的一行。
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    /// 行内容（不含行首的 +/-/空格 前缀，不含换行）
+    pub content: String,
+}
+
+impl DiffLine {
+    fn render_into(&self, out: &mut String) {
+        let prefix = match self.kind {
+            LineKind::Context => ' ',
+            LineKind::Added => '+',
+            LineKind::Removed => '-',
+            LineKind::NoNewlineAtEof => {
+                out.push_str("\\ No newline at end of file\n");
+                return;
+            }
+        };
+        out.push(prefix);
+        out.push_str(&self.content);
+        out.push('\n');
     }
 }
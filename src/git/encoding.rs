@@ -0,0 +1,82 @@
+use encoding_rs::{Encoding, GBK, UTF_8, WINDOWS_1252};
+
+/// 推测文本内容的编码：优先识别 BOM，其次依次尝试 UTF-8 和 GBK，
+/// 都不像时按 Latin-1（windows-1252）解码，后者不会失败
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    if looks_like_gb2312(bytes)
+        && GBK
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .is_some()
+    {
+        return GBK;
+    }
+    WINDOWS_1252
+}
+
+/// 常见的 Latin-1 文本（如 `M\xfcller`）大多也是合法的 GBK：重音字母加 ASCII 字母恰好是一个 GBK 双字节。
+/// 因此只有绝大多数双字节字符落在 GB2312 的符号区和汉字区（两个字节都在 0xA1–0xFE，
+/// 首字节不超过 0xF7）时才当作 GBK
+fn looks_like_gb2312(bytes: &[u8]) -> bool {
+    let (mut total, mut common) = (0usize, 0usize);
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i];
+        if lead < 0x80 {
+            i += 1;
+            continue;
+        }
+        let Some(&trail) = bytes.get(i + 1) else {
+            return false;
+        };
+        total += 1;
+        if (0xA1..=0xF7).contains(&lead) && (0xA1..=0xFE).contains(&trail) {
+            common += 1;
+        }
+        i += 2;
+    }
+    total > 0 && common * 10 >= total * 9
+}
+
+/// 按指定编码解码，UTF-8 合法时直接使用
+pub fn decode(bytes: &[u8], encoding: &'static Encoding) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latin1_is_not_mistaken_for_gbk() {
+        let bytes = b"name = \"M\xfcller\"";
+        let encoding = detect(bytes);
+        assert_eq!(encoding, WINDOWS_1252);
+        assert_eq!(decode(bytes, encoding), "name = \"Müller\"");
+    }
+
+    #[test]
+    fn detects_gbk_chinese() {
+        let (bytes, _, _) = GBK.encode("// 读取配置文件，失败时使用默认值。");
+        let encoding = detect(&bytes);
+        assert_eq!(encoding, GBK);
+        assert_eq!(
+            decode(&bytes, encoding),
+            "// 读取配置文件，失败时使用默认值。"
+        );
+    }
+
+    #[test]
+    fn prefers_bom_and_utf8() {
+        assert_eq!(detect("名字 = 1".as_bytes()), UTF_8);
+        assert_eq!(detect(b"\xff\xfea\x00"), encoding_rs::UTF_16LE);
+    }
+}
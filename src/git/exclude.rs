@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::error::AppError;
+
+/// 仓库级的排除规则文件名，语法与 .gitignore 相同
+pub const IGNORE_FILE: &str = ".llmcignore";
+
+/// 判断文件是否应从 prompt 中排除（只保留变更行数统计）。
+///
+/// 规则来源：
+/// - 仓库根目录的 `.llmcignore`
+/// - 配置文件 `[diff] exclude` 中的 gitignore 风格模式
+/// - `.gitattributes` 中的 `-diff`、`linguist-generated`、`linguist-vendored`
+pub struct Excluder {
+    patterns: Gitignore,
+}
+
+impl Excluder {
+    pub fn new(repo_root: &Path, extra_patterns: &[String]) -> Result<Self, AppError> {
+        let mut builder = GitignoreBuilder::new(repo_root);
+        let ignore_file = repo_root.join(IGNORE_FILE);
+        if ignore_file.exists()
+            && let Some(e) = builder.add(&ignore_file)
+        {
+            return Err(AppError::Config(format!(
+                "无法解析 {}: {}",
+                ignore_file.display(),
+                e
+            )));
+        }
+        for pattern in extra_patterns {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| AppError::Config(format!("无效的排除规则 {}: {}", pattern, e)))?;
+        }
+        let patterns = builder
+            .build()
+            .map_err(|e| AppError::Config(format!("无法构建排除规则: {}", e)))?;
+        Ok(Self { patterns })
+    }
+
+    /// path 为相对仓库根目录的路径
+    pub fn is_excluded(&self, repo: &Repository, path: &str) -> bool {
+        if self
+            .patterns
+            .matched_path_or_any_parents(path, false)
+            .is_ignore()
+        {
+            return true;
+        }
+
+        let attr = |name: &str| {
+            let value = repo
+                .get_attr(Path::new(path), name, AttrCheckFlags::default())
+                .ok()
+                .flatten();
+            AttrValue::from_string(value)
+        };
+        matches!(attr("diff"), AttrValue::False)
+            || is_set(attr("linguist-generated"))
+            || is_set(attr("linguist-vendored"))
+    }
+}
+
+/// linguist 属性既可以写成 `linguist-generated` 也可以写成 `linguist-generated=true`
+fn is_set(value: AttrValue) -> bool {
+    match value {
+        AttrValue::True => true,
+        AttrValue::String(s) => s == "true",
+        _ => false,
+    }
+}
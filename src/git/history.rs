@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::{Commit, Oid, Repository, Sort, StatusOptions};
+
+use super::signing::{self, Signer};
+use crate::error::AppError;
+
+/// 备份引用的前缀，重写历史前原来的 HEAD 保存在其下
+const BACKUP_REF_PREFIX: &str = "refs/llmc/backup/";
+
+/// 提交范围中的一个提交。
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    /// 完整的提交 SHA
+    pub id: String,
+    /// 7 位短 SHA
+    pub short_id: String,
+    /// 提交的 message
+    pub message: String,
+}
+
+/// 工作区和暂存区是否没有未提交的修改（未跟踪文件不影响重写历史）
+pub fn is_clean(repo: &Repository) -> Result<bool, AppError> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut opts))
+        .map_err(|e| AppError::Git(format!("无法获取工作区状态: {}", e)))?;
+    Ok(statuses.is_empty())
+}
+
+/// 解析 `<base>..<head>` 或 `<base>`（即 `<base>..HEAD`），按从旧到新的顺序列出范围内的提交。
+/// 范围必须以 HEAD 结尾且不含合并提交，这样才能直接在当前分支上重放
+pub fn commits_in_range(repo: &Repository, range: &str) -> Result<Vec<CommitInfo>, AppError> {
+    let (base, head) = match range.split_once("..") {
+        Some((base, head)) if !head.is_empty() => (base, head),
+        Some((base, _)) => (base, "HEAD"),
+        None => (range, "HEAD"),
+    };
+    let resolve = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|o| o.peel_to_commit())
+            .map(|c| c.id())
+            .map_err(|e| AppError::Git(format!("无法解析提交 {}: {}", spec, e)))
+    };
+    let base = resolve(base)?;
+    let head = resolve(head)?;
+    if head != head_commit(repo)?.id() {
+        return Err(AppError::Git(
+            "只支持重写以 HEAD 结尾的提交范围，请先切换到对应的分支".into(),
+        ));
+    }
+
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| AppError::Git(format!("无法遍历提交: {}", e)))?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .and_then(|_| walk.push(head))
+        .and_then(|_| walk.hide(base))
+        .map_err(|e| AppError::Git(format!("无法遍历提交: {}", e)))?;
+
+    let mut commits = Vec::new();
+    for id in walk {
+        let commit = id
+            .and_then(|id| repo.find_commit(id))
+            .map_err(|e| AppError::Git(format!("无法读取提交: {}", e)))?;
+        if commit.parent_count() > 1 {
+            return Err(AppError::Git(format!(
+                "范围内包含合并提交 {}，暂不支持重写",
+                short(commit.id())
+            )));
+        }
+        commits.push(CommitInfo {
+            id: commit.id().to_string(),
+            short_id: short(commit.id()),
+            message: commit.message().unwrap_or_default().to_string(),
+        });
+    }
+    Ok(commits)
+}
+
+/// 把当前 HEAD 保存为备份引用，返回引用名称。
+/// 名称由纳秒时间戳和 HEAD 的短 SHA 组成，连续执行也不会冲突
+pub fn backup_head(repo: &Repository) -> Result<String, AppError> {
+    let head = head_commit(repo)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let backup = format!("{}{}-{}", BACKUP_REF_PREFIX, timestamp, short(head.id()));
+    repo.reference(&backup, head.id(), false, "llmc reword: backup")
+        .map_err(|e| AppError::Git(format!("无法创建备份引用: {}", e)))?;
+    Ok(backup)
+}
+
+/// 删除 backup_head 创建的备份引用
+pub fn delete_backup(repo: &Repository, backup: &str) -> Result<(), AppError> {
+    repo.find_reference(backup)
+        .and_then(|mut r| r.delete())
+        .map_err(|e| AppError::Git(format!("无法删除备份引用 {}: {}", backup, e)))
+}
+
+/// 从第一个被改写的提交开始，按从旧到新的顺序重放提交：tree 与作者保持不变，message 使用 messages 中的新值
+/// （没有新值的沿用原 message），提交者更新为当前用户
+pub fn rewrite_messages(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    messages: &HashMap<String, String>,
+) -> Result<(), AppError> {
+    let git_err = |what: &str, e: git2::Error| AppError::Git(format!("{}: {}", what, e));
+    let head = head_commit(repo)?;
+    let committer = repo
+        .signature()
+        .map_err(|e| git_err("无法获取签名信息", e))?;
+    // 开启 commit.gpgsign 时重放的提交同样需要签名
+    let signer = Signer::from_config(repo)?;
+
+    // 原提交 id 到重放后提交 id 的映射，用于改写后续提交的父提交
+    let mut rewritten: HashMap<Oid, Oid> = HashMap::new();
+    let mut tip = head.id();
+    for info in commits {
+        let id = Oid::from_str(&info.id).map_err(|e| git_err("无效的提交 id", e))?;
+        let commit = repo
+            .find_commit(id)
+            .map_err(|e| git_err("无法读取提交", e))?;
+        // 第一个被改写的提交之前的提交保持原样，不必重放
+        let message = messages.get(&info.id);
+        if message.is_none() && !commit.parent_ids().any(|p| rewritten.contains_key(&p)) {
+            continue;
+        }
+        let parents = commit
+            .parent_ids()
+            .map(|p| rewritten.get(&p).copied().unwrap_or(p))
+            .map(|p| repo.find_commit(p))
+            .collect::<Result<Vec<Commit>, _>>()
+            .map_err(|e| git_err("无法读取父提交", e))?;
+        let parents: Vec<&Commit> = parents.iter().collect();
+        let tree = commit.tree().map_err(|e| git_err("无法读取 tree", e))?;
+        let message = message
+            .map(String::as_str)
+            .unwrap_or(commit.message().unwrap_or_default());
+
+        tip = signing::create_commit(
+            repo,
+            signer.as_ref(),
+            &commit.author(),
+            &committer,
+            message,
+            &tree,
+            &parents,
+        )?;
+        rewritten.insert(id, tip);
+    }
+
+    // HEAD 指向分支时移动分支，处于分离状态时直接移动 HEAD
+    let mut head_ref = repo.head().map_err(|e| git_err("无法读取 HEAD", e))?;
+    head_ref
+        .set_target(tip, "llmc reword")
+        .map_err(|e| git_err("无法更新 HEAD", e))?;
+    Ok(())
+}
+
+/// 获取 HEAD 指向的提交
+pub fn head_commit(repo: &Repository) -> Result<Commit<'_>, AppError> {
+    repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| AppError::Git(format!("无法读取 HEAD 提交: {}", e)))
+}
+
+fn short(id: Oid) -> String {
+    id.to_string()[..7].to_string()
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use git2::Repository;
+
+use crate::error::AppError;
+
+/// 仓库的 git 钩子，目录由 `core.hooksPath` 决定，未配置时为 `.git/hooks`。
+pub struct Hooks {
+    dir: PathBuf,
+    workdir: PathBuf,
+    index: PathBuf,
+}
+
+impl Hooks {
+    pub fn new(repo: &Repository) -> Result<Self, AppError> {
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| AppError::Git("无法获取仓库工作目录（可能是 bare 仓库）".into()))?
+            .to_path_buf();
+        // 使用 repo 当前的 index（--all 时为临时 index），钩子中的 git 命令通过 GIT_INDEX_FILE 读写它
+        let index = repo
+            .index()
+            .ok()
+            .and_then(|index| index.path().map(Path::to_path_buf))
+            .unwrap_or_else(|| repo.path().join("index"));
+        Ok(Self {
+            dir: hooks_dir(repo, &workdir),
+            workdir,
+            index,
+        })
+    }
+
+    /// 在工作区根目录执行钩子，钩子不存在或不可执行时直接跳过。
+    /// 钩子的输出直接显示在终端，退出码非零时返回错误
+    pub fn run(&self, name: &str, args: &[&str]) -> Result<(), AppError> {
+        let path = self.dir.join(name);
+        if !is_executable(&path) {
+            return Ok(());
+        }
+        let status = Command::new(&path)
+            .args(args)
+            .current_dir(&self.workdir)
+            .env("GIT_INDEX_FILE", &self.index)
+            .stdin(Stdio::null())
+            .status()
+            .map_err(|e| AppError::Git(format!("无法执行 {} 钩子: {}", name, e)))?;
+        if !status.success() {
+            let code = status
+                .code()
+                .map_or_else(|| "被信号终止".to_string(), |c| format!("退出码 {}", c));
+            return Err(AppError::Git(format!("{} 钩子失败（{}）", name, code)));
+        }
+        Ok(())
+    }
+}
+
+/// llmc 安装的钩子中的标记行，卸载时据此确认钩子是 llmc 生成的
+const HOOK_MARKER: &str = "# llmc prepare-commit-msg hook";
+
+/// prepare-commit-msg 钩子脚本，`{command}` 替换为 llmc 命令。
+/// 合并、squash、复用已有提交（--amend、-c、-C）以及已经提供了 message（-m、-F、非空模板）时跳过；
+/// 判断是否已有 message 时忽略注释行和 git commit -v 在剪切线之后附加的 diff。
+/// 生成失败不影响提交，用户仍可以在编辑器中手写
+const HOOK_SCRIPT: &str = r#"#!/bin/sh
+{marker}
+# 由 llmc hook install 生成，使用 llmc hook uninstall 删除
+
+case "$2" in
+    merge|squash|commit) exit 0 ;;
+esac
+
+if sed '/^# -* >8 -*$/,$d' "$1" | grep -v '^#' | grep -q '[^[:space:]]'; then
+    exit 0
+fi
+
+{command} --message-file "$1" >/dev/null || true
+"#;
+
+impl Hooks {
+    /// 写入 prepare-commit-msg 钩子并设为可执行。已有非 llmc 生成的钩子时，只有 force 才覆盖
+    pub fn install_prepare_commit_msg(
+        &self,
+        command: &str,
+        force: bool,
+    ) -> Result<PathBuf, AppError> {
+        let path = self.dir.join("prepare-commit-msg");
+        if !force && path.exists() && !is_llmc_hook(&path) {
+            return Err(AppError::Git(format!(
+                "{} 已存在，使用 --force 覆盖",
+                path.display()
+            )));
+        }
+        fs::create_dir_all(&self.dir)?;
+        let script = HOOK_SCRIPT
+            .replace("{marker}", HOOK_MARKER)
+            .replace("{command}", command);
+        fs::write(&path, script)?;
+        set_executable(&path)?;
+        Ok(path)
+    }
+
+    /// 删除 llmc 生成的 prepare-commit-msg 钩子，不会删除其他工具或用户自己的钩子
+    pub fn uninstall_prepare_commit_msg(&self) -> Result<PathBuf, AppError> {
+        let path = self.dir.join("prepare-commit-msg");
+        if !path.exists() {
+            return Err(AppError::Git(format!("{} 不存在", path.display())));
+        }
+        if !is_llmc_hook(&path) {
+            return Err(AppError::Git(format!(
+                "{} 不是 llmc 安装的钩子，未删除",
+                path.display()
+            )));
+        }
+        fs::remove_file(&path)?;
+        Ok(path)
+    }
+}
+
+fn is_llmc_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.lines().any(|l| l == HOOK_MARKER))
+}
+
+/// 与 git 一致：相对路径的 `core.hooksPath` 相对于工作区根目录
+fn hooks_dir(repo: &Repository, workdir: &Path) -> PathBuf {
+    let configured = repo
+        .config()
+        .and_then(|mut c| c.snapshot())
+        .and_then(|c| c.get_path("core.hooksPath"))
+        .ok();
+    match configured {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => workdir.join(dir),
+        None => common_dir(repo).join("hooks"),
+    }
+}
+
+/// 链接工作树的 git 目录位于 `.git/worktrees/<name>`，钩子在主仓库的 git 目录中
+fn common_dir(repo: &Repository) -> PathBuf {
+    let git_dir = repo.path();
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(dir) => git_dir.join(dir.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::fs;
+
+use git2::{Index, IndexAddOption, Repository};
+use tempfile::TempPath;
+
+use crate::error::AppError;
+
+/// `--all` 使用的临时 index，与 git commit -a 一致：复制暂存区后暂存工作区的变更。
+/// pre-commit、diff 和提交都基于它，提交成功后才替换 `.git/index`；
+/// 未提交（用户取消或钩子失败）时在 drop 时删除，暂存区保持不变
+pub struct TempIndex {
+    path: TempPath,
+}
+
+impl TempIndex {
+    /// 暂存已跟踪文件的修改和删除，include_untracked 为 true 时再添加未被忽略的新文件
+    pub(super) fn stage_all(repo: &Repository, include_untracked: bool) -> Result<Self, AppError> {
+        let path = tempfile::Builder::new()
+            .prefix("index.llmc-")
+            .tempfile_in(repo.path())?
+            .into_temp_path();
+        // 还没有暂存过任何文件的仓库没有 index，从空 index 开始
+        let current = repo.path().join("index");
+        if current.exists() {
+            fs::copy(&current, &path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+
+        let temp = Self { path };
+        temp.attach(repo)?;
+        let mut index = repo
+            .index()
+            .map_err(|e| AppError::Git(format!("无法获取 index: {}", e)))?;
+        index
+            .update_all(["*"], None)
+            .map_err(|e| AppError::Git(format!("无法暂存已跟踪文件: {}", e)))?;
+        if include_untracked {
+            index
+                .add_all(["*"], IndexAddOption::DEFAULT, None)
+                .map_err(|e| AppError::Git(format!("无法暂存未跟踪文件: {}", e)))?;
+        }
+        index
+            .write()
+            .map_err(|e| AppError::Git(format!("无法写入 index: {}", e)))?;
+        Ok(temp)
+    }
+
+    /// 重新读取临时 index 并设为 repo 的 index，之后 `repo.index()` 和钩子使用的都是它
+    pub(super) fn attach(&self, repo: &Repository) -> Result<(), AppError> {
+        let mut index =
+            Index::open(&self.path).map_err(|e| AppError::Git(format!("无法读取 index: {}", e)))?;
+        repo.set_index(&mut index)
+            .map_err(|e| AppError::Git(format!("无法设置 index: {}", e)))
+    }
+
+    /// 提交成功后用临时 index 替换暂存区
+    pub(super) fn persist(self, repo: &Repository) -> Result<(), AppError> {
+        self.path
+            .persist(repo.path().join("index"))
+            .map_err(|e| AppError::Git(format!("无法更新 index: {}", e.error)))
+    }
+}
@@ -0,0 +1,78 @@
+use super::FileDiff;
+use super::binary::{format_size, kind_by_extension, size_change};
+
+/// LFS 指针文件的首行
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+
+/// Git LFS 指针文件中记录的对象信息。
+struct Pointer {
+    /// 对象哈希，如 `sha256:4d7a...`
+    oid: String,
+    size: u64,
+}
+
+impl Pointer {
+    fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        if lines.next()?.trim() != POINTER_VERSION {
+            return None;
+        }
+        let (mut oid, mut size) = (None, None);
+        for line in lines {
+            if let Some(value) = line.strip_prefix("oid ") {
+                oid = Some(value.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("size ") {
+                size = value.trim().parse().ok();
+            }
+        }
+        Some(Self {
+            oid: oid?,
+            size: size?,
+        })
+    }
+
+    /// 缩短后的哈希，如 `sha256:4d7a2140`
+    fn short_oid(&self) -> &str {
+        let end = self.oid.find(':').map_or(8, |i| i + 9).min(self.oid.len());
+        &self.oid[..end]
+    }
+}
+
+/// 任一版本是 LFS 指针文件时，描述被跟踪对象的变化，如
+/// `Git LFS .psd file, sha256:4d7a2140 -> sha256:9c1e0b7f, 1.2 MiB -> 1.3 MiB (+100.0 KiB)`
+pub fn summarize(path: &str, old: Option<&str>, new: Option<&str>) -> Option<String> {
+    let old = old.and_then(Pointer::parse);
+    let new = new.and_then(Pointer::parse);
+    let kind = kind_by_extension(path);
+    let summary = match (old, new) {
+        (None, None) => return None,
+        (None, Some(new)) => format!(
+            "Git LFS {} added, {}, {}",
+            kind,
+            new.short_oid(),
+            format_size(new.size)
+        ),
+        (Some(old), None) => format!(
+            "Git LFS {} removed, was {}, {}",
+            kind,
+            old.short_oid(),
+            format_size(old.size)
+        ),
+        (Some(old), Some(new)) => format!(
+            "Git LFS {}, {} -> {}, {}",
+            kind,
+            old.short_oid(),
+            new.short_oid(),
+            size_change(old.size, new.size)
+        ),
+    };
+    Some(summary)
+}
+
+/// diff 中是否出现 LFS 指针文件的首行（指针文件只有三行，首行总在上下文中）
+pub fn mentions_pointer(file: &FileDiff) -> bool {
+    file.hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .any(|line| line.content == POINTER_VERSION)
+}
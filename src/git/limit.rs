@@ -0,0 +1,225 @@
+use super::{FileDiff, LineKind, render_files};
+use crate::tokenizer::Tokenizer;
+
+/// 锁文件与常见生成文件，compact 策略会优先省略它们
+const GENERATED_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Pipfile.lock",
+    "composer.lock",
+    "Gemfile.lock",
+    "go.sum",
+];
+
+/// 生成文件的后缀
+const GENERATED_SUFFIXES: &[&str] = &[".min.js", ".min.css", ".map", ".pb.go", "_pb2.py", ".snap"];
+
+/// 为适应 token 限制而缩减后的 diff。
+pub struct ReducedDiff {
+    /// 缩减后的 diff 文本
+    pub text: String,
+    /// 被完全省略或部分截断的文件
+    pub elided: Vec<String>,
+}
+
+impl ReducedDiff {
+    /// 渲染发送给 LLM 的 diff，末尾注明被省略的文件
+    pub fn render(&self) -> String {
+        if self.elided.is_empty() {
+            return self.text.clone();
+        }
+        format!(
+            "{}\nNote: the following files were omitted or truncated to fit the token limit: {}\n",
+            self.text,
+            self.elided.join(", ")
+        )
+    }
+}
+
+/// 按文件截断：每个文件分得剩余预算的平均份额，在 hunk 边界处截断，
+/// 未用完的预算顺延给后面的文件。
+pub fn truncate(files: &[FileDiff], budget: usize, tokenizer: &dyn Tokenizer) -> ReducedDiff {
+    let files: Vec<&FileDiff> = files.iter().collect();
+    truncate_files(&files, budget, tokenizer, Vec::new())
+}
+
+/// 先省略锁文件和生成文件（已替换为摘要的除外），再去掉上下文行，仍然超限时按文件截断。
+pub fn compact(files: &[FileDiff], budget: usize, tokenizer: &dyn Tokenizer) -> ReducedDiff {
+    let (generated, files): (Vec<&FileDiff>, Vec<&FileDiff>) = files
+        .iter()
+        .partition(|f| f.summary.is_none() && is_generated(f.path()));
+    let elided: Vec<String> = generated.iter().map(|f| f.path().to_string()).collect();
+
+    let text: String = files.iter().map(|f| f.render()).collect();
+    if tokenizer.count_tokens(&text) <= budget {
+        return ReducedDiff { text, elided };
+    }
+
+    let stripped: Vec<FileDiff> = files.iter().map(|f| strip_context(f)).collect();
+    let text = render_files(&stripped);
+    if tokenizer.count_tokens(&text) <= budget {
+        return ReducedDiff { text, elided };
+    }
+
+    let stripped: Vec<&FileDiff> = stripped.iter().collect();
+    truncate_files(&stripped, budget, tokenizer, elided)
+}
+
+/// 用每个文件的变更统计和 hunk 头替代完整 diff。
+pub fn summarize(files: &[FileDiff], budget: usize, tokenizer: &dyn Tokenizer) -> ReducedDiff {
+    let mut text = String::new();
+    let mut used = 0;
+    let mut elided = Vec::new();
+    for file in files {
+        let mut entry = format!(
+            "{}: +{} -{} lines\n",
+            file.path(),
+            file.insertions,
+            file.deletions
+        );
+        for hunk in &file.hunks {
+            entry.push_str("  ");
+            entry.push_str(&hunk.header);
+            entry.push('\n');
+        }
+        let tokens = tokenizer.count_tokens(&entry);
+        if used + tokens <= budget {
+            used += tokens;
+            text.push_str(&entry);
+        } else {
+            elided.push(file.path().to_string());
+        }
+    }
+    ReducedDiff { text, elided }
+}
+
+/// 在 budget 允许的范围内附加被修改函数的完整源码，放不下的函数跳过。
+/// 没有可附加的函数时返回空字符串
+pub fn attach_functions(files: &[FileDiff], budget: usize, tokenizer: &dyn Tokenizer) -> String {
+    let heading = "\nFull source of the changed functions:\n";
+    let mut used = tokenizer.count_tokens(heading);
+    let mut text = String::new();
+    for file in files {
+        for symbol in &file.symbols {
+            let Some(source) = &symbol.source else {
+                continue;
+            };
+            let entry = format!("\n{}: {}\n{}\n", file.path(), symbol.name, source);
+            let tokens = tokenizer.count_tokens(&entry);
+            if used + tokens <= budget {
+                used += tokens;
+                text.push_str(&entry);
+            }
+        }
+    }
+    if text.is_empty() {
+        return text;
+    }
+    format!("{}{}", heading, text)
+}
+
+/// 将 diff 切分为不超过 budget 的片段，用于 map_reduce 策略。
+/// 小文件合并到同一片段；超出预算的文件按 hunk 分组拆开，每组都带上文件头。
+pub fn chunk(files: &[FileDiff], budget: usize, tokenizer: &dyn Tokenizer) -> Vec<String> {
+    // 先拆成不可再分的单元：完整的小文件，或大文件的一组 hunk
+    let mut units: Vec<(String, usize)> = Vec::new();
+    for file in files {
+        let whole = file.render();
+        let tokens = tokenizer.count_tokens(&whole);
+        if tokens <= budget {
+            units.push((whole, tokens));
+            continue;
+        }
+
+        let header = file.render_header();
+        let header_tokens = tokenizer.count_tokens(&header);
+        let mut group = header.clone();
+        let mut group_tokens = header_tokens;
+        for hunk in &file.hunks {
+            let hunk = hunk.render();
+            let tokens = tokenizer.count_tokens(&hunk);
+            if group_tokens > header_tokens && group_tokens + tokens > budget {
+                units.push((std::mem::replace(&mut group, header.clone()), group_tokens));
+                group_tokens = header_tokens;
+            }
+            group.push_str(&hunk);
+            group_tokens += tokens;
+        }
+        units.push((group, group_tokens));
+    }
+
+    // 再把单元按顺序装箱
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+    for (unit, tokens) in units {
+        if !current.is_empty() && current_tokens + tokens > budget {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        current.push_str(&unit);
+        current_tokens += tokens;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn truncate_files(
+    files: &[&FileDiff],
+    budget: usize,
+    tokenizer: &dyn Tokenizer,
+    mut elided: Vec<String>,
+) -> ReducedDiff {
+    let mut text = String::new();
+    let mut remaining = budget;
+    for (i, file) in files.iter().enumerate() {
+        let share = remaining / (files.len() - i);
+        let header = file.render_header();
+        let header_tokens = tokenizer.count_tokens(&header);
+        if header_tokens > share {
+            elided.push(file.path().to_string());
+            continue;
+        }
+
+        let mut used = header_tokens;
+        let mut kept = header;
+        let mut complete = true;
+        for hunk in &file.hunks {
+            let hunk = hunk.render();
+            let tokens = tokenizer.count_tokens(&hunk);
+            if used + tokens > share {
+                complete = false;
+                break;
+            }
+            used += tokens;
+            kept.push_str(&hunk);
+        }
+        if !complete {
+            elided.push(file.path().to_string());
+        }
+        text.push_str(&kept);
+        remaining -= used;
+    }
+    ReducedDiff { text, elided }
+}
+
+/// 去掉 hunk 中的上下文行，只保留 hunk 头和增删行
+fn strip_context(file: &FileDiff) -> FileDiff {
+    let mut file = file.clone();
+    for hunk in &mut file.hunks {
+        hunk.lines.retain(|line| line.kind != LineKind::Context);
+    }
+    file
+}
+
+/// 按文件名判断是否为锁文件或生成文件
+pub fn is_generated(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    GENERATED_FILES.contains(&file_name)
+        || GENERATED_SUFFIXES.iter().any(|s| file_name.ends_with(s))
+}
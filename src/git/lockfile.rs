@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 依赖名到版本集合的映射（同一依赖可能同时存在多个版本）
+type Packages = BTreeMap<String, BTreeSet<String>>;
+
+/// 摘要中最多列出的依赖变更条数
+const MAX_ENTRIES: usize = 50;
+
+/// 支持解析的锁文件格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockfileKind {
+    Cargo,
+    Npm,
+    Yarn,
+    Poetry,
+    GoSum,
+}
+
+impl LockfileKind {
+    /// 按文件名识别锁文件
+    pub fn detect(path: &str) -> Option<Self> {
+        match path.rsplit('/').next().unwrap_or(path) {
+            "Cargo.lock" => Some(Self::Cargo),
+            "package-lock.json" | "npm-shrinkwrap.json" => Some(Self::Npm),
+            "yarn.lock" => Some(Self::Yarn),
+            "poetry.lock" => Some(Self::Poetry),
+            "go.sum" => Some(Self::GoSum),
+            _ => None,
+        }
+    }
+
+    fn parse(self, content: &str) -> Option<Packages> {
+        match self {
+            Self::Cargo | Self::Poetry => parse_toml_packages(content),
+            Self::Npm => parse_npm(content),
+            Self::Yarn => Some(parse_yarn(content)),
+            Self::GoSum => Some(parse_go_sum(content)),
+        }
+    }
+}
+
+/// 比较锁文件新旧两个版本，生成依赖变更摘要，
+/// 如 "serde 1.0.197 -> 1.0.210, added tokio-util 0.7.11"。
+/// old/new 为 None 表示文件不存在（新增或删除）；任一版本解析失败时返回 None。
+pub fn summarize(kind: LockfileKind, old: Option<&str>, new: Option<&str>) -> Option<String> {
+    let old = old.map_or(Some(Packages::new()), |c| kind.parse(c))?;
+    let new = new.map_or(Some(Packages::new()), |c| kind.parse(c))?;
+
+    let mut changes = Vec::new();
+    for (name, new_versions) in &new {
+        match old.get(name) {
+            None => changes.push(format!("added {} {}", name, join(new_versions))),
+            Some(old_versions) if old_versions != new_versions => {
+                changes.push(format!(
+                    "{} {} -> {}",
+                    name,
+                    join(old_versions),
+                    join(new_versions)
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, old_versions) in &old {
+        if !new.contains_key(name) {
+            changes.push(format!("removed {} {}", name, join(old_versions)));
+        }
+    }
+
+    if changes.is_empty() {
+        return Some("dependency versions unchanged".into());
+    }
+    let total = changes.len();
+    changes.truncate(MAX_ENTRIES);
+    let mut summary = format!("dependency changes: {}", changes.join(", "));
+    if total > MAX_ENTRIES {
+        summary.push_str(&format!(", and {} more", total - MAX_ENTRIES));
+    }
+    Some(summary)
+}
+
+fn join(versions: &BTreeSet<String>) -> String {
+    versions.iter().cloned().collect::<Vec<_>>().join("/")
+}
+
+fn insert(packages: &mut Packages, name: &str, version: &str) {
+    packages
+        .entry(name.to_string())
+        .or_default()
+        .insert(version.to_string());
+}
+
+/// Cargo.lock 与 poetry.lock：`[[package]]` 数组中的 name/version
+fn parse_toml_packages(content: &str) -> Option<Packages> {
+    let value: toml::Value = toml::from_str(content).ok()?;
+    let mut packages = Packages::new();
+    for package in value.get("package")?.as_array()? {
+        if let (Some(name), Some(version)) = (
+            package.get("name").and_then(|v| v.as_str()),
+            package.get("version").and_then(|v| v.as_str()),
+        ) {
+            insert(&mut packages, name, version);
+        }
+    }
+    Some(packages)
+}
+
+/// package-lock.json：v2/v3 使用 `packages`，v1 使用嵌套的 `dependencies`
+fn parse_npm(content: &str) -> Option<Packages> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    let mut packages = Packages::new();
+    if let Some(entries) = value["packages"].as_object() {
+        for (key, entry) in entries {
+            // 根项目的 key 为空字符串
+            let Some(pos) = key.rfind("node_modules/") else {
+                continue;
+            };
+            if let Some(version) = entry["version"].as_str() {
+                insert(&mut packages, &key[pos + "node_modules/".len()..], version);
+            }
+        }
+    } else {
+        collect_npm_v1(&value["dependencies"], &mut packages);
+    }
+    Some(packages)
+}
+
+fn collect_npm_v1(deps: &serde_json::Value, packages: &mut Packages) {
+    let Some(deps) = deps.as_object() else {
+        return;
+    };
+    for (name, entry) in deps {
+        if let Some(version) = entry["version"].as_str() {
+            insert(packages, name, version);
+        }
+        collect_npm_v1(&entry["dependencies"], packages);
+    }
+}
+
+/// yarn.lock（v1 与 berry）：顶格的描述行如 `"@babel/core@^7.0.0", "@babel/core@^7.1.0":`，
+/// 随后缩进的 `version "7.1.2"` 或 `version: 7.1.2`
+fn parse_yarn(content: &str) -> Packages {
+    let mut packages = Packages::new();
+    let mut current: Option<String> = None;
+    for line in content.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            current = line
+                .trim_end_matches(':')
+                .split(',')
+                .next()
+                .map(|spec| spec.trim().trim_matches('"'))
+                .and_then(|spec| spec.rfind('@').filter(|&i| i > 0).map(|i| &spec[..i]))
+                .map(str::to_string);
+            continue;
+        }
+        let trimmed = line.trim();
+        if let (Some(name), Some(version)) = (
+            &current,
+            trimmed
+                .strip_prefix("version:")
+                .or_else(|| trimmed.strip_prefix("version ")),
+        ) {
+            insert(&mut packages, name, version.trim().trim_matches('"'));
+        }
+    }
+    packages
+}
+
+/// go.sum：每行为 `<module> <version>[/go.mod] <hash>`
+fn parse_go_sum(content: &str) -> Packages {
+    let mut packages = Packages::new();
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            insert(&mut packages, module, version.trim_end_matches("/go.mod"));
+        }
+    }
+    packages
+}
@@ -1,7 +1,27 @@
+mod binary;
 mod diff;
+mod encoding;
+mod exclude;
+mod history;
+mod hooks;
+mod index;
+mod lfs;
+pub mod limit;
+mod lockfile;
+mod notebook;
 mod repository;
+mod rust_api;
+mod semantic;
+mod signing;
+mod submodule;
+mod symbols;
 mod traits;
 
-pub use diff::DiffResult;
+pub use diff::{
+    DiffLine, DiffResult, DiffScope, FileDiff, FileStatus, Hunk, LineKind, Symbol, render_files,
+};
+pub use exclude::Excluder;
+pub use history::CommitInfo;
+pub use index::TempIndex;
 pub use repository::GitRepository;
 pub use traits::GitOperations;
@@ -0,0 +1,176 @@
+use git2::{DiffOptions, Patch};
+use serde_json::Value;
+
+/// 笔记本中的一个单元格，只保留类型和源码，忽略输出、执行计数和元数据。
+#[derive(Debug, PartialEq)]
+struct Cell {
+    id: Option<String>,
+    kind: String,
+    source: String,
+}
+
+/// 单元格层面的一处变化，下标从 0 开始
+enum CellChange<'a> {
+    Added(usize, &'a Cell),
+    Removed(usize, &'a Cell),
+    Modified(usize, &'a Cell, &'a Cell),
+}
+
+/// 是否为 Jupyter 笔记本
+pub fn is_notebook(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".ipynb")
+}
+
+/// 比较笔记本新旧两个版本，逐个单元格列出源码变化，如
+/// `cell 4 (code) modified:` 后接该单元格源码的行 diff。
+/// old/new 为 None 表示文件不存在；任一版本解析失败时返回 None
+pub fn summarize(old: Option<&str>, new: Option<&str>) -> Option<String> {
+    let old = old.map_or(Some(Vec::new()), parse)?;
+    let new = new.map_or(Some(Vec::new()), parse)?;
+
+    let changes = diff_cells(&old, &new);
+    if changes.is_empty() {
+        return Some("outputs or metadata only, no source changes".into());
+    }
+
+    let mut summary = String::from("notebook changes:");
+    for change in changes {
+        match change {
+            CellChange::Added(i, cell) => {
+                summary.push_str(&format!("\n  cell {} ({}) added:", i + 1, cell.kind));
+                push_source_diff(&mut summary, "", &cell.source);
+            }
+            CellChange::Removed(i, cell) => {
+                summary.push_str(&format!("\n  cell {} ({}) removed:", i + 1, cell.kind));
+                push_source_diff(&mut summary, &cell.source, "");
+            }
+            CellChange::Modified(i, old, new) => {
+                let kind = if old.kind == new.kind {
+                    new.kind.clone()
+                } else {
+                    format!("{} -> {}", old.kind, new.kind)
+                };
+                summary.push_str(&format!("\n  cell {} ({}) modified:", i + 1, kind));
+                push_source_diff(&mut summary, &old.source, &new.source);
+            }
+        }
+    }
+    Some(summary)
+}
+
+fn parse(content: &str) -> Option<Vec<Cell>> {
+    let value: Value = serde_json::from_str(content).ok()?;
+    let cells = value.get("cells")?.as_array()?;
+    Some(
+        cells
+            .iter()
+            .map(|cell| Cell {
+                id: cell["id"].as_str().map(str::to_string),
+                kind: cell["cell_type"].as_str().unwrap_or("code").to_string(),
+                // source 可以是字符串，也可以是按行拆分的字符串数组
+                source: match &cell["source"] {
+                    Value::String(s) => s.clone(),
+                    Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+                    _ => String::new(),
+                },
+            })
+            .collect(),
+    )
+}
+
+/// 对齐新旧单元格。所有单元格都带 id（nbformat 4.5+）时按 id 匹配，
+/// 否则以源码完全相同的单元格为锚点，锚点之间的单元格按位置配对
+fn diff_cells<'a>(old: &'a [Cell], new: &'a [Cell]) -> Vec<CellChange<'a>> {
+    let mut changes = Vec::new();
+    if old.iter().chain(new).all(|cell| cell.id.is_some()) {
+        for (j, cell) in new.iter().enumerate() {
+            match old.iter().find(|o| o.id == cell.id) {
+                Some(o) if o != cell => changes.push(CellChange::Modified(j, o, cell)),
+                Some(_) => {}
+                None => changes.push(CellChange::Added(j, cell)),
+            }
+        }
+        for (i, cell) in old.iter().enumerate() {
+            if !new.iter().any(|n| n.id == cell.id) {
+                changes.push(CellChange::Removed(i, cell));
+            }
+        }
+        return changes;
+    }
+
+    let mut anchors = common_cells(old, new);
+    anchors.push((old.len(), new.len()));
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in anchors {
+        while i < next_i && j < next_j {
+            changes.push(CellChange::Modified(j, &old[i], &new[j]));
+            i += 1;
+            j += 1;
+        }
+        changes.extend((i..next_i).map(|k| CellChange::Removed(k, &old[k])));
+        changes.extend((j..next_j).map(|k| CellChange::Added(k, &new[k])));
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    changes
+}
+
+/// 源码与类型都相同的单元格的最长公共子序列，返回下标对
+fn common_cells(old: &[Cell], new: &[Cell]) -> Vec<(usize, usize)> {
+    let same = |a: &Cell, b: &Cell| a.kind == b.kind && a.source == b.source;
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if same(&old[i], &new[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if same(&old[i], &new[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// 追加单元格源码的行 diff（不含 hunk 头）
+fn push_source_diff(out: &mut String, old: &str, new: &str) {
+    let mut opts = DiffOptions::new();
+    opts.context_lines(1);
+    let Ok(patch) =
+        Patch::from_buffers(old.as_bytes(), None, new.as_bytes(), None, Some(&mut opts))
+    else {
+        return;
+    };
+    for h in 0..patch.num_hunks() {
+        for l in 0..patch.num_lines_in_hunk(h).unwrap_or(0) {
+            let Ok(line) = patch.line_in_hunk(h, l) else {
+                continue;
+            };
+            let prefix = match line.origin() {
+                '+' => '+',
+                '-' => '-',
+                ' ' => ' ',
+                _ => continue,
+            };
+            let content = String::from_utf8_lossy(line.content());
+            out.push_str(&format!(
+                "\n    {}{}",
+                prefix,
+                content.trim_end_matches(['\r', '\n'])
+            ));
+        }
+    }
+}
@@ -1,17 +1,196 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
 
-use git2::{DiffOptions, Repository};
+use git2::{
+    Delta, Diff, DiffFile, DiffFindOptions, DiffOptions, Index, Oid, Patch, Repository, Signature,
+    Tree,
+};
 
-use super::{DiffResult, GitOperations};
+use super::history::{self, CommitInfo};
+use super::hooks::Hooks;
+use super::lockfile::{self, LockfileKind};
+use super::semantic::{self, StructuredFormat};
+use super::signing::{self, Signer};
+use super::submodule::{self, GITLINK_MODE};
+use super::{
+    DiffLine, DiffResult, DiffScope, Excluder, FileDiff, FileStatus, GitOperations, Hunk, LineKind,
+    TempIndex,
+};
+use super::{binary, encoding, lfs, notebook, rust_api, symbols};
+use crate::config::DiffConfig;
 use crate::error::AppError;
 
 /// 基于 git2 的 GitOperations 实现。
 #[derive(Default)]
-pub struct GitRepository;
+pub struct GitRepository {
+    diff_config: DiffConfig,
+}
 
 impl GitRepository {
-    pub fn new() -> Self {
-        Self
+    pub fn new(diff_config: DiffConfig) -> Self {
+        Self { diff_config }
+    }
+
+    /// 检测重命名后逐个文件生成结构化 diff，并按文件类型替换为摘要或补充上下文。
+    /// base_tree 为 diff 旧一侧的 tree，用于查找库 crate 的公开模块
+    fn collect(
+        &self,
+        repo: &Repository,
+        mut diff: Diff,
+        include_untracked: bool,
+        base_tree: Option<&Tree>,
+    ) -> Result<DiffResult, AppError> {
+        // 检测重命名和复制，避免把重命名显示为整文件删除 + 新增
+        let mut find_opts = DiffFindOptions::new();
+        find_opts
+            .renames(true)
+            .rename_threshold(self.diff_config.rename_threshold)
+            .copies(self.diff_config.detect_copies)
+            .copy_threshold(self.diff_config.rename_threshold);
+        if include_untracked {
+            find_opts.for_untracked(true);
+        }
+        diff.find_similar(Some(&mut find_opts))
+            .map_err(|e| AppError::Git(format!("无法检测重命名: {}", e)))?;
+
+        let excluder = Excluder::new(
+            repo.workdir().unwrap_or(repo.path()),
+            &self.diff_config.exclude,
+        )?;
+        let mut library = rust_api::LibraryFiles::new(|path: &str| {
+            let entry = base_tree?.get_path(Path::new(path)).ok()?;
+            let blob = entry.to_object(repo).ok()?.peel_to_blob().ok()?;
+            String::from_utf8(blob.content().to_vec()).ok()
+        });
+        let mut files = Vec::new();
+        for idx in 0..diff.deltas().len() {
+            let patch = Patch::from_diff(&diff, idx)
+                .map_err(|e| AppError::Git(format!("无法生成 patch: {}", e)))?;
+            if let Some(mut patch) = patch {
+                let mut file = file_diff(&mut patch)?;
+                // libgit2 把带 -diff 属性的文本文件也标记为二进制。与 git 一致按内容判断：
+                // 前 8000 字节含 NUL 才是二进制，文本文件继续交给锁文件、排除规则等分支处理
+                let contents = file.binary.then(|| {
+                    let delta = patch.delta();
+                    (
+                        blob_bytes(repo, &delta.old_file()),
+                        blob_bytes(repo, &delta.new_file()),
+                    )
+                });
+                if let Some((old, new)) = &contents {
+                    file.binary = [old, new]
+                        .into_iter()
+                        .flatten()
+                        .any(|c| binary::is_binary(c));
+                    if !file.binary {
+                        // libgit2 没有为它们生成 hunk，强制按文本重新比较，
+                        // 被排除时的行数统计和其他分支使用的 diff 才是准确的
+                        let mut opts = DiffOptions::new();
+                        opts.force_text(true);
+                        let mut text = Patch::from_buffers(
+                            old.as_deref().unwrap_or_default(),
+                            file.old_path.as_deref().map(Path::new),
+                            new.as_deref().unwrap_or_default(),
+                            file.new_path.as_deref().map(Path::new),
+                            Some(&mut opts),
+                        )
+                        .map_err(|e| AppError::Git(format!("无法生成 patch: {}", e)))?;
+                        let text = file_diff(&mut text)?;
+                        file.insertions = text.insertions;
+                        file.deletions = text.deletions;
+                        file.hunks = text.hunks;
+                    }
+                }
+                if file.binary
+                    && let Some((old, new)) = &contents
+                {
+                    // 二进制文件没有可读的 diff，报告类型和大小变化
+                    let summary = binary::summarize(file.path(), old.as_deref(), new.as_deref());
+                    file.replace_with_summary(summary);
+                } else if let Some(kind) = LockfileKind::detect(file.path())
+                    && let Some(summary) = {
+                        let delta = patch.delta();
+                        let old = blob_text(repo, &delta.old_file());
+                        let new = blob_text(repo, &delta.new_file());
+                        lockfile::summarize(kind, old.as_deref(), new.as_deref())
+                    }
+                {
+                    // 锁文件用依赖版本变化替代原始 diff。先于排除规则处理：
+                    // 锁文件通常带有 -diff 或 linguist-generated 属性
+                    file.replace_with_summary(summary);
+                } else if excluder.is_excluded(repo, file.path()) {
+                    // 被排除的文件只保留统计，让模型知道它们有变更
+                    let changed = file.insertions + file.deletions;
+                    file.replace_with_summary(format!("{} lines changed (omitted)", changed));
+                } else if file.old_mode == GITLINK_MODE || file.new_mode == GITLINK_MODE {
+                    // 子模块指针变化只有两个 SHA，改为列出其间的提交标题
+                    let delta = patch.delta();
+                    let gitlink = |mode: u32, id: Oid| {
+                        if mode == GITLINK_MODE {
+                            id
+                        } else {
+                            Oid::zero()
+                        }
+                    };
+                    let old = gitlink(file.old_mode, delta.old_file().id());
+                    let new = gitlink(file.new_mode, delta.new_file().id());
+                    let summary = submodule::summarize(repo, file.path(), old, new);
+                    file.replace_with_summary(summary);
+                } else if lfs::mentions_pointer(&file) {
+                    // LFS 指针文件的 diff 只有哈希，改为描述被跟踪对象的变化
+                    let delta = patch.delta();
+                    let old = blob_text(repo, &delta.old_file());
+                    let new = blob_text(repo, &delta.new_file());
+                    if let Some(summary) =
+                        lfs::summarize(file.path(), old.as_deref(), new.as_deref())
+                    {
+                        file.replace_with_summary(summary);
+                    }
+                } else if notebook::is_notebook(file.path()) {
+                    // 笔记本只比较单元格源码，忽略输出、执行计数和元数据
+                    let delta = patch.delta();
+                    let old = blob_text(repo, &delta.old_file());
+                    let new = blob_text(repo, &delta.new_file());
+                    if let Some(summary) = notebook::summarize(old.as_deref(), new.as_deref()) {
+                        file.replace_with_summary(summary);
+                    }
+                } else if self.diff_config.semantic
+                    && let Some(format) = StructuredFormat::detect(file.path())
+                {
+                    // 结构化配置文件用键路径级别的变更替代行 diff，
+                    // 新增和删除的文件仍保留原始内容
+                    let delta = patch.delta();
+                    if let (Some(old), Some(new)) = (
+                        blob_text(repo, &delta.old_file()),
+                        blob_text(repo, &delta.new_file()),
+                    ) && let Some(summary) = semantic::summarize(format, &old, &new)
+                    {
+                        file.replace_with_summary(summary);
+                    }
+                } else if self.diff_config.symbols {
+                    // 用语法树标注每个 hunk 所在的函数，并列出被修改的符号
+                    let delta = patch.delta();
+                    let old = blob_text(repo, &delta.old_file());
+                    let new = blob_text(repo, &delta.new_file());
+                    symbols::annotate(&mut file, old.as_deref(), new.as_deref());
+                }
+                // 比较库 crate 公开模块中的公开项，找出可能破坏兼容性的变化
+                if let Some(old_path) = &file.old_path
+                    && library.contains(old_path)
+                {
+                    let delta = patch.delta();
+                    if let Some(old) = blob_text(repo, &delta.old_file()) {
+                        let new = blob_text(repo, &delta.new_file());
+                        file.breaking_changes = rust_api::breaking_changes(&old, new.as_deref());
+                    }
+                }
+                files.push(file);
+            }
+        }
+
+        Ok(DiffResult::new(files))
     }
 }
 
@@ -25,65 +204,633 @@ impl GitOperations for GitRepository {
         Ok(workdir.to_path_buf())
     }
 
-    fn staged_diff(&self, repo_path: &Path) -> Result<DiffResult, AppError> {
+    fn diff(
+        &self,
+        repo_path: &Path,
+        scope: DiffScope,
+        index: Option<&TempIndex>,
+    ) -> Result<DiffResult, AppError> {
         let repo = Repository::open(repo_path)
             .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        if let Some(index) = index {
+            index.attach(&repo)?;
+        }
 
-        // 获取 HEAD tree（如果是空仓库则为 None）
-        let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        // diff 旧一侧的 tree：HEAD tree（空仓库为 None），--amend 时为 HEAD 的父提交（HEAD 是根提交时为空 tree）。
+        // --preview 的旧一侧是暂存区，查找公开模块时以 HEAD 为准
+        let base_tree = match scope {
+            DiffScope::Amend => history::head_commit(&repo)?
+                .parent(0)
+                .ok()
+                .and_then(|p| p.tree().ok()),
+            _ => repo.head().ok().and_then(|h| h.peel_to_tree().ok()),
+        };
 
         let mut opts = DiffOptions::new();
-        let diff = repo
-            .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
-            .map_err(|e| AppError::Git(format!("无法获取 staged diff: {}", e)))?;
-
-        let stats = diff
-            .stats()
-            .map_err(|e| AppError::Git(format!("无法获取 diff 统计: {}", e)))?;
-        let files_changed = stats.files_changed();
-
-        // 收集 diff 文本
-        let mut raw = String::new();
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            let origin = line.origin();
-            if origin == '+' || origin == '-' || origin == ' ' {
-                raw.push(origin);
+        let diff = match scope {
+            DiffScope::Staged => {
+                // 作为 git 钩子运行时（如 git commit -a），git 通过 GIT_INDEX_FILE 指定临时的 index
+                let index = match env::var_os("GIT_INDEX_FILE") {
+                    Some(path) => Some(
+                        Index::open(Path::new(&path))
+                            .map_err(|e| AppError::Git(format!("无法读取 index: {}", e)))?,
+                    ),
+                    None => None,
+                };
+                repo.diff_tree_to_index(base_tree.as_ref(), index.as_ref(), Some(&mut opts))
+            }
+            DiffScope::Amend => repo.diff_tree_to_index(base_tree.as_ref(), None, Some(&mut opts)),
+            // 工作区的变更已由 stage_all 暂存到临时 index
+            DiffScope::All { .. } if index.is_some() => {
+                repo.diff_tree_to_index(base_tree.as_ref(), None, Some(&mut opts))
             }
-            if let Ok(content) = std::str::from_utf8(line.content()) {
-                raw.push_str(content);
+            DiffScope::All { include_untracked } => {
+                untracked_options(&mut opts, include_untracked);
+                repo.diff_tree_to_workdir_with_index(base_tree.as_ref(), Some(&mut opts))
             }
-            true
+            DiffScope::Unstaged { include_untracked } => {
+                untracked_options(&mut opts, include_untracked);
+                repo.diff_index_to_workdir(None, Some(&mut opts))
+            }
+        };
+        let diff = diff.map_err(|e| AppError::Git(format!("无法获取 diff: {}", e)))?;
+        self.collect(&repo, diff, scope.include_untracked(), base_tree.as_ref())
+    }
+
+    fn commit_diff(&self, repo_path: &Path, id: &str) -> Result<DiffResult, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        let commit = Oid::from_str(id)
+            .and_then(|id| repo.find_commit(id))
+            .map_err(|e| AppError::Git(format!("无法读取提交 {}: {}", id, e)))?;
+        let tree = commit
+            .tree()
+            .map_err(|e| AppError::Git(format!("无法读取 tree: {}", e)))?;
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+
+        let mut opts = DiffOptions::new();
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))
+            .map_err(|e| AppError::Git(format!("无法获取 diff: {}", e)))?;
+        self.collect(&repo, diff, false, parent_tree.as_ref())
+    }
+
+    fn is_clean(&self, repo_path: &Path) -> Result<bool, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        history::is_clean(&repo)
+    }
+
+    fn commits_in_range(&self, repo_path: &Path, range: &str) -> Result<Vec<CommitInfo>, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        history::commits_in_range(&repo, range)
+    }
+
+    fn backup_head(&self, repo_path: &Path) -> Result<String, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        history::backup_head(&repo)
+    }
+
+    fn delete_backup(&self, repo_path: &Path, backup: &str) -> Result<(), AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        history::delete_backup(&repo, backup)
+    }
+
+    fn rewrite_messages(
+        &self,
+        repo_path: &Path,
+        commits: &[CommitInfo],
+        messages: &HashMap<String, String>,
+    ) -> Result<(), AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        history::rewrite_messages(&repo, commits, messages)
+    }
+
+    fn stage_all(&self, repo_path: &Path, include_untracked: bool) -> Result<TempIndex, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        TempIndex::stage_all(&repo, include_untracked)
+    }
+
+    fn install_hook(
+        &self,
+        repo_path: &Path,
+        command: &str,
+        force: bool,
+    ) -> Result<PathBuf, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        Hooks::new(&repo)?.install_prepare_commit_msg(command, force)
+    }
+
+    fn uninstall_hook(&self, repo_path: &Path) -> Result<PathBuf, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        Hooks::new(&repo)?.uninstall_prepare_commit_msg()
+    }
+
+    fn run_pre_commit(&self, repo_path: &Path, index: Option<&TempIndex>) -> Result<(), AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        if let Some(index) = index {
+            index.attach(&repo)?;
+        }
+        Hooks::new(&repo)?.run("pre-commit", &[]).map_err(|e| {
+            AppError::Git(format!(
+                "{}，已中止提交",
+                e.to_string().trim_start_matches("Git error: ")
+            ))
         })
-        .map_err(|e| AppError::Git(format!("无法输出 diff: {}", e)))?;
+    }
 
-        Ok(DiffResult::new(raw, files_changed))
+    fn head_message(&self, repo_path: &Path) -> Result<String, AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        let head = history::head_commit(&repo)?;
+        Ok(head.message().unwrap_or_default().to_string())
     }
 
-    fn commit(&self, repo_path: &Path, message: &str) -> Result<(), AppError> {
+    fn amend(&self, repo_path: &Path, message: &str, no_verify: bool) -> Result<(), AppError> {
         let repo = Repository::open(repo_path)
             .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        let head = history::head_commit(&repo)?;
 
         let sig = repo
             .signature()
             .map_err(|e| AppError::Git(format!("无法获取签名信息: {}", e)))?;
 
-        let tree_id = repo
-            .index()
-            .map_err(|e| AppError::Git(format!("无法获取 index: {}", e)))?
-            .write_tree()
-            .map_err(|e| AppError::Git(format!("无法写入 tree: {}", e)))?;
+        let parents = head.parents().collect::<Vec<_>>();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+
+        // 保留原作者，提交者更新为当前用户，与 git commit --amend 一致
+        commit_with_hooks(&repo, message, no_verify, |message, tree| {
+            let reflog = format!("commit (amend): {}", subject(message));
+            write_commit(
+                &repo,
+                &head.author(),
+                &sig,
+                message,
+                tree,
+                &parents,
+                &reflog,
+            )
+        })
+    }
+
+    fn commit(
+        &self,
+        repo_path: &Path,
+        message: &str,
+        no_verify: bool,
+        index: Option<TempIndex>,
+    ) -> Result<(), AppError> {
+        let repo = Repository::open(repo_path)
+            .map_err(|e| AppError::Git(format!("无法打开仓库: {}", e)))?;
+        // 重新读取临时 index，包含 pre-commit 钩子暂存的修改
+        if let Some(index) = &index {
+            index.attach(&repo)?;
+        }
 
-        let tree = repo
-            .find_tree(tree_id)
-            .map_err(|e| AppError::Git(format!("无法查找 tree: {}", e)))?;
+        let sig = repo
+            .signature()
+            .map_err(|e| AppError::Git(format!("无法获取签名信息: {}", e)))?;
 
         // 获取 parent commit（如果存在）
         let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
         let parents: Vec<&git2::Commit> = parent.iter().collect();
 
-        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
-            .map_err(|e| AppError::Git(format!("提交失败: {}", e)))?;
+        commit_with_hooks(&repo, message, no_verify, |message, tree| {
+            let kind = if parent.is_some() {
+                "commit"
+            } else {
+                "commit (initial)"
+            };
+            let reflog = format!("{}: {}", kind, subject(message));
+            write_commit(&repo, &sig, &sig, message, tree, &parents, &reflog)?;
+            // 与 git commit -a 一致，提交成功后暂存区与新的 HEAD 一致
+            match index {
+                Some(index) => index.persist(&repo),
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+/// 创建提交并移动 HEAD。开启 `commit.gpgsign` 时先对提交内容签名，再以签名提交写入
+fn write_commit(
+    repo: &Repository,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[&git2::Commit],
+    reflog: &str,
+) -> Result<(), AppError> {
+    let signer = Signer::from_config(repo)?;
+    let id = signing::create_commit(
+        repo,
+        signer.as_ref(),
+        author,
+        committer,
+        message,
+        tree,
+        parents,
+    )?;
+
+    // HEAD 指向分支（包括尚无提交的分支）时移动分支，处于分离状态时直接移动 HEAD
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| AppError::Git(format!("无法读取 HEAD: {}", e)))?;
+    match head.symbolic_target() {
+        Some(branch) => repo.reference(branch, id, true, reflog).map(|_| ()),
+        None => repo.set_head_detached(id),
+    }
+    .map_err(|e| AppError::Git(format!("无法更新 HEAD: {}", e)))
+}
+
+/// message 的第一行，用于 reflog
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or_default()
+}
+
+/// 按 git commit 的顺序执行 message 相关的钩子并提交：prepare-commit-msg → commit-msg → 提交 → post-commit。
+/// pre-commit 在生成 message 之前由 run_pre_commit 单独执行。
+/// message 写入 `.git/COMMIT_EDITMSG` 交给钩子，钩子修改后的内容作为最终的 message；
+/// no_verify 时跳过 commit-msg，与 git commit --no-verify 一致
+fn commit_with_hooks(
+    repo: &Repository,
+    message: &str,
+    no_verify: bool,
+    create: impl FnOnce(&str, &Tree) -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    let hooks = Hooks::new(repo)?;
+    let message_file = repo.path().join("COMMIT_EDITMSG");
+    // 与 git 一致，交给钩子的 message 以换行结尾，方便钩子追加 trailer
+    let mut content = message.trim_end().to_string();
+    content.push('\n');
+    fs::write(&message_file, content)?;
+    let message_arg = message_file.to_string_lossy();
+    let aborted = |e: AppError| {
+        AppError::Git(format!(
+            "{}，已中止提交。commit message 已保存在 {}，可用 git commit -F 重新提交",
+            e.to_string().trim_start_matches("Git error: "),
+            message_file.display()
+        ))
+    };
+
+    hooks
+        .run("prepare-commit-msg", &[&message_arg, "message"])
+        .map_err(aborted)?;
+    if !no_verify {
+        hooks.run("commit-msg", &[&message_arg]).map_err(aborted)?;
+    }
+    let message = fs::read_to_string(&message_file)?;
+    if message.trim().is_empty() {
+        return Err(AppError::Git("commit message 为空，已中止提交".into()));
+    }
+
+    let tree_id = repo
+        .index()
+        .map_err(|e| AppError::Git(format!("无法获取 index: {}", e)))?
+        .write_tree()
+        .map_err(|e| AppError::Git(format!("无法写入 tree: {}", e)))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| AppError::Git(format!("无法查找 tree: {}", e)))?;
+
+    create(&message, &tree)?;
+
+    // post-commit 的退出码不影响提交结果，与 git 一致
+    hooks.run("post-commit", &[]).ok();
+    Ok(())
+}
+
+/// 生成工作区 diff 时是否包含未跟踪文件
+fn untracked_options(opts: &mut DiffOptions, include_untracked: bool) {
+    opts.include_untracked(include_untracked)
+        .recurse_untracked_dirs(include_untracked)
+        .show_untracked_content(include_untracked);
+}
+
+/// 读取 diff 一侧的文件内容，文件不存在时返回 None。
+/// 对象库中没有的内容（工作区的修改）从工作目录读取
+fn blob_bytes(repo: &Repository, file: &DiffFile) -> Option<Vec<u8>> {
+    if !file.exists() {
+        return None;
+    }
+    if let Ok(blob) = repo.find_blob(file.id()) {
+        return Some(blob.content().to_vec());
+    }
+    std::fs::read(repo.workdir()?.join(file.path()?)).ok()
+}
+
+/// 读取 blob 的文本内容，文件不存在或不是有效 UTF-8 时返回 None
+fn blob_text(repo: &Repository, file: &DiffFile) -> Option<String> {
+    String::from_utf8(blob_bytes(repo, file)?).ok()
+}
+
+/// 将 git2 的 Patch 转换为结构化的 FileDiff
+fn file_diff(patch: &mut Patch) -> Result<FileDiff, AppError> {
+    let delta = patch.delta();
+    let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().into_owned());
+
+    let status = match delta.status() {
+        Delta::Added | Delta::Untracked => FileStatus::Added,
+        Delta::Deleted => FileStatus::Deleted,
+        Delta::Renamed => FileStatus::Renamed,
+        Delta::Copied => FileStatus::Copied,
+        Delta::Typechange => FileStatus::TypeChange,
+        _ => FileStatus::Modified,
+    };
+    let old_path = (status != FileStatus::Added)
+        .then(|| path_of(delta.old_file()))
+        .flatten();
+    let new_path = (status != FileStatus::Deleted)
+        .then(|| path_of(delta.new_file()))
+        .flatten();
+
+    // git2 未暴露 delta 的相似度，从 libgit2 渲染的 patch 头中读取
+    let similarity = if matches!(status, FileStatus::Renamed | FileStatus::Copied) {
+        patch.to_buf().ok().and_then(|buf| {
+            String::from_utf8_lossy(&buf)
+                .lines()
+                .take_while(|l| !l.starts_with("@@"))
+                .find_map(|l| l.strip_prefix("similarity index "))
+                .and_then(|v| v.trim_end_matches('%').parse().ok())
+        })
+    } else {
+        None
+    };
+
+    // 先收集原始字节，整个文件统一推测编码，避免 GBK 等非 UTF-8 文本变成乱码
+    let mut raw_hunks = Vec::new();
+    let mut text = Vec::new();
+    for h in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch
+            .hunk(h)
+            .map_err(|e| AppError::Git(format!("无法读取 hunk: {}", e)))?;
+        let mut lines = Vec::with_capacity(line_count);
+        for l in 0..line_count {
+            let line = patch
+                .line_in_hunk(h, l)
+                .map_err(|e| AppError::Git(format!("无法读取 diff 行: {}", e)))?;
+            text.extend_from_slice(line.content());
+            lines.push((line.origin(), line.content().to_vec()));
+        }
+        raw_hunks.push((
+            hunk.header().to_vec(),
+            hunk.old_start(),
+            hunk.old_lines(),
+            hunk.new_start(),
+            hunk.new_lines(),
+            lines,
+        ));
+    }
+    let encoding = encoding::detect(&text);
+
+    let mut hunks = Vec::with_capacity(raw_hunks.len());
+    let (mut insertions, mut deletions) = (0, 0);
+    for (header, old_start, old_lines, new_start, new_lines, raw_lines) in raw_hunks {
+        let mut lines = Vec::with_capacity(raw_lines.len());
+        for (origin, content) in raw_lines {
+            let kind = match origin {
+                '+' => {
+                    insertions += 1;
+                    LineKind::Added
+                }
+                '-' => {
+                    deletions += 1;
+                    LineKind::Removed
+                }
+                '=' | '>' | '<' => LineKind::NoNewlineAtEof,
+                _ => LineKind::Context,
+            };
+            let content = encoding::decode(&content, encoding);
+            lines.push(DiffLine {
+                kind,
+                content: content.trim_end_matches(['\r', '\n']).to_string(),
+            });
+        }
+        hunks.push(Hunk {
+            header: encoding::decode(&header, encoding).trim_end().to_string(),
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            lines,
+        });
+    }
+
+    Ok(FileDiff {
+        old_path,
+        new_path,
+        status,
+        old_mode: u32::from(delta.old_file().mode()),
+        new_mode: u32::from(delta.new_file().mode()),
+        similarity,
+        binary: delta.flags().is_binary(),
+        summary: None,
+        symbols: Vec::new(),
+        breaking_changes: Vec::new(),
+        insertions,
+        deletions,
+        hunks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录中提交 base，再暂存 changes，返回暂存区 diff
+    fn staged_diff(base: &[(&str, &[u8])], changes: &[(&str, &[u8])]) -> DiffResult {
+        let (dir, repo) = init_repo(base);
+        stage(&repo, changes);
+        GitRepository::default()
+            .diff(dir.path(), DiffScope::Staged, None)
+            .unwrap()
+    }
+
+    /// 在临时目录中创建仓库并提交 base
+    fn init_repo(base: &[(&str, &[u8])]) -> (tempfile::TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Tester").unwrap();
+        config.set_str("user.email", "tester@example.com").unwrap();
+
+        stage(&repo, base);
+        let sig = repo.signature().unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+        (dir, repo)
+    }
+
+    /// 暂存区中的文件名
+    fn staged_paths(repo: &Repository) -> Vec<String> {
+        let mut index = repo.index().unwrap();
+        index.read(true).unwrap();
+        index
+            .iter()
+            .map(|e| String::from_utf8(e.path).unwrap())
+            .collect()
+    }
+
+    fn stage(repo: &Repository, files: &[(&str, &[u8])]) {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            let path_on_disk = workdir.join(path);
+            fs::create_dir_all(path_on_disk.parent().unwrap()).unwrap();
+            fs::write(path_on_disk, content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+    }
+
+    fn cargo_lock(serde: &str) -> Vec<u8> {
+        format!(
+            "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"{}\"\n",
+            serde
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn summarizes_lockfile_marked_as_no_diff() {
+        let old = cargo_lock("1.0.197");
+        let new = cargo_lock("1.0.210");
+        let diff = staged_diff(
+            &[
+                (".gitattributes", b"Cargo.lock -diff\n"),
+                ("Cargo.lock", &old),
+            ],
+            &[("Cargo.lock", &new)],
+        );
+        let file = &diff.files[0];
+        assert!(!file.binary);
+        assert_eq!(
+            file.summary.as_deref(),
+            Some("dependency changes: serde 1.0.197 -> 1.0.210")
+        );
+    }
+
+    #[test]
+    fn counts_lines_of_excluded_files() {
+        let diff = staged_diff(
+            &[
+                (
+                    ".gitattributes",
+                    b"data.csv -diff\nschema.rs linguist-generated\n",
+                ),
+                ("data.csv", b"id,name\n1,a\n"),
+                ("schema.rs", b"pub struct A;\n"),
+            ],
+            &[
+                ("data.csv", b"id,name\n1,b\n2,c\n"),
+                ("schema.rs", b"pub struct A;\npub struct B;\n"),
+            ],
+        );
+        let summaries: Vec<_> = diff
+            .files
+            .iter()
+            .map(|f| (f.path(), f.summary.as_deref()))
+            .collect();
+        assert_eq!(
+            summaries,
+            [
+                ("data.csv", Some("3 lines changed (omitted)")),
+                ("schema.rs", Some("1 lines changed (omitted)")),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_breaking_changes_only_in_public_modules() {
+        let diff = staged_diff(
+            &[
+                ("src/lib.rs", b"pub mod api;\nmod internal;\n"),
+                ("src/api.rs", b"pub fn get() {}\n"),
+                ("src/internal.rs", b"pub fn helper() {}\n"),
+                ("src/main.rs", b"pub fn run() {}\nfn main() {}\n"),
+            ],
+            &[
+                ("src/api.rs", b"pub fn get(id: u32) {}\n"),
+                ("src/internal.rs", b"pub fn helper2() {}\n"),
+                ("src/main.rs", b"fn main() {}\n"),
+            ],
+        );
+        assert_eq!(
+            diff.breaking_changes(),
+            ["src/api.rs: changed `fn get`: `pub fn get()` -> `pub fn get(id: u32)`"]
+        );
+    }
+
+    #[test]
+    fn summarizes_binary_content() {
+        let diff = staged_diff(
+            &[("logo.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")],
+            &[("logo.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0")],
+        );
+        let file = &diff.files[0];
+        assert!(file.binary);
+        assert_eq!(
+            file.summary.as_deref(),
+            Some("binary PNG image, 16 B -> 18 B (+2 B)")
+        );
+    }
+
+    #[test]
+    fn commit_all_keeps_index_until_committed() {
+        let (dir, repo) = init_repo(&[("a.txt", b"a\n")]);
+        let workdir = dir.path();
+        fs::write(workdir.join("a.txt"), "b\n").unwrap();
+        fs::write(workdir.join("new.txt"), "new\n").unwrap();
+        let git = GitRepository::default();
+        let scope = DiffScope::All {
+            include_untracked: true,
+        };
+
+        // 取消提交：暂存区不变，临时 index 被删除
+        let index = git.stage_all(workdir, true).unwrap();
+        let diff = git.diff(workdir, scope, Some(&index)).unwrap();
+        assert_eq!(diff.files_changed, 2);
+        assert_eq!(staged_paths(&repo), ["a.txt"]);
+        drop(index);
+        let leftovers = fs::read_dir(repo.path())
+            .unwrap()
+            .filter(|e| {
+                let name = e.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with("index.llmc-")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
 
-        Ok(())
+        // 提交后暂存区替换为临时 index
+        let index = git.stage_all(workdir, true).unwrap();
+        git.commit(workdir, "update", true, Some(index)).unwrap();
+        assert_eq!(staged_paths(&repo), ["a.txt", "new.txt"]);
+        let head = repo.head().unwrap().peel_to_tree().unwrap();
+        let blob = head
+            .get_path(Path::new("a.txt"))
+            .unwrap()
+            .to_object(&repo)
+            .unwrap()
+            .peel_to_blob()
+            .unwrap();
+        assert_eq!(blob.content(), b"b\n");
+        assert!(
+            git.diff(workdir, DiffScope::Staged, None)
+                .unwrap()
+                .raw
+                .is_empty()
+        );
     }
 }
@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use tree_sitter::{Node, Parser, Tree};
+
+/// 公开 API 中的一项，如 `fn Parser::parse`、`field Config.name`。
+struct Item {
+    /// 规范化后的签名，用于比较
+    signature: String,
+    /// 新增该项也会破坏兼容性时的原因，如必须实现的 trait 方法
+    breaking_if_added: Option<&'static str>,
+}
+
+/// 公开项的键（如 `fn Parser::parse`）到签名的映射
+type Api = BTreeMap<String, Item>;
+
+/// 库 crate 中可能包含公开 API 的源文件：从 `src/lib.rs` 经 `pub mod` 可达的文件。
+/// 私有模块、没有 lib.rs 的 crate 以及二进制入口、测试、示例中的 `pub` 不算公开 API。
+/// read 按仓库内路径读取文件内容，每个 crate 只解析一次
+pub struct LibraryFiles<F> {
+    read: F,
+    /// lib.rs 路径到可达文件的映射
+    crates: HashMap<String, HashSet<String>>,
+}
+
+impl<F: Fn(&str) -> Option<String>> LibraryFiles<F> {
+    pub fn new(read: F) -> Self {
+        Self {
+            read,
+            crates: HashMap::new(),
+        }
+    }
+
+    /// path 是否参与公开 API 比较
+    pub fn contains(&mut self, path: &str) -> bool {
+        if !path.ends_with(".rs") {
+            return false;
+        }
+        // 由近及远查找路径中的 `src` 目录，第一个有 lib.rs 的即为所属的库 crate
+        let mut src_dirs: Vec<&str> = path
+            .match_indices("src/")
+            .filter(|&(i, _)| i == 0 || path.as_bytes()[i - 1] == b'/')
+            .map(|(i, _)| &path[..i + 3])
+            .collect();
+        src_dirs.reverse();
+        for src in src_dirs {
+            let lib = format!("{}/lib.rs", src);
+            if !self.crates.contains_key(&lib) {
+                let Some(source) = (self.read)(&lib) else {
+                    continue;
+                };
+                let mut files = HashSet::new();
+                self.visit(&lib, &source, &mut files);
+                self.crates.insert(lib.clone(), files);
+            }
+            return self.crates[&lib].contains(path);
+        }
+        false
+    }
+
+    fn visit(&self, path: &str, source: &str, files: &mut HashSet<String>) {
+        if !files.insert(path.to_string()) {
+            return;
+        }
+        let Some(tree) = parse(source) else {
+            return;
+        };
+        // lib.rs 与 mod.rs 的子模块在同一目录，`foo.rs` 的子模块在 `foo/` 下
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let dir = match name {
+            "lib.rs" | "mod.rs" => parent.to_string(),
+            _ => join(parent, name.trim_end_matches(".rs")),
+        };
+        self.modules(tree.root_node(), source, &dir, files);
+    }
+
+    /// 查找 `pub mod` 声明：`pub mod name;` 读取对应的文件，内联模块继续查找其中的声明
+    fn modules(&self, parent: Node, source: &str, dir: &str, files: &mut HashSet<String>) {
+        let mut cursor = parent.walk();
+        for node in parent.named_children(&mut cursor) {
+            if node.kind() != "mod_item" || !is_pub(node, source) {
+                continue;
+            }
+            let Some(name) = node
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            else {
+                continue;
+            };
+            if let Some(body) = node.child_by_field_name("body") {
+                self.modules(body, source, &join(dir, name), files);
+                continue;
+            }
+            let candidates = [
+                format!("{}.rs", join(dir, name)),
+                format!("{}/mod.rs", join(dir, name)),
+            ];
+            if let Some((path, content)) = candidates
+                .into_iter()
+                .find_map(|path| (self.read)(&path).map(|content| (path, content)))
+            {
+                self.visit(&path, &content, files);
+            }
+        }
+    }
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// 比较 Rust 源文件新旧两个版本的公开项，找出可能破坏兼容性的变化：
+/// 删除的公开项、签名变化，以及新增的必须实现的 trait 方法和穷尽枚举的变体。
+/// new 为 None 表示文件被删除；任一版本解析失败时返回空列表
+pub fn breaking_changes(old: &str, new: Option<&str>) -> Vec<String> {
+    let (Some(old), Some(new)) = (public_api(old), new.map_or(Some(Api::new()), public_api)) else {
+        return Vec::new();
+    };
+
+    let mut changes = Vec::new();
+    for (key, old_item) in &old {
+        match new.get(key) {
+            None => changes.push(format!("removed `{}`", key)),
+            Some(new_item) if new_item.signature != old_item.signature => {
+                changes.push(format!(
+                    "changed `{}`: `{}` -> `{}`",
+                    key, old_item.signature, new_item.signature
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, new_item) in &new {
+        if let Some(reason) = new_item.breaking_if_added
+            && !old.contains_key(key)
+        {
+            changes.push(format!("added `{}` ({})", key, reason));
+        }
+    }
+    changes
+}
+
+fn parse(source: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_rust::LANGUAGE.into())
+        .ok()?;
+    parser.parse(source, None)
+}
+
+/// 只有不带限定的 `pub` 才是公开 API，`pub(crate)` 等不算
+fn is_pub(node: Node, source: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|c| c.kind() == "visibility_modifier" && c.utf8_text(source.as_bytes()) == Ok("pub"))
+}
+
+fn public_api(source: &str) -> Option<Api> {
+    let tree = parse(source)?;
+    let mut api = Api::new();
+    Collector {
+        source,
+        api: &mut api,
+    }
+    .items(tree.root_node(), "");
+    Some(api)
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    api: &'a mut Api,
+}
+
+impl Collector<'_> {
+    fn text(&self, node: Node) -> &str {
+        node.utf8_text(self.source.as_bytes()).unwrap_or_default()
+    }
+
+    fn field(&self, node: Node, name: &str) -> Option<&str> {
+        node.child_by_field_name(name).map(|n| self.text(n))
+    }
+
+    fn is_pub(&self, node: Node) -> bool {
+        is_pub(node, self.source)
+    }
+
+    /// 节点在 body 之前的部分，即声明签名，空白统一压缩为单个空格
+    fn signature(&self, node: Node) -> String {
+        let end = node
+            .child_by_field_name("body")
+            .map_or(node.end_byte(), |body| body.start_byte());
+        normalize(&self.source[node.start_byte()..end])
+    }
+
+    /// 紧邻节点之前的属性中是否有 `#[non_exhaustive]`
+    fn is_non_exhaustive(&self, node: Node) -> bool {
+        let mut prev = node.prev_named_sibling();
+        while let Some(sibling) = prev {
+            match sibling.kind() {
+                "attribute_item" if self.text(sibling).contains("non_exhaustive") => return true,
+                "attribute_item" | "line_comment" | "block_comment" => {
+                    prev = sibling.prev_named_sibling();
+                }
+                _ => break,
+            }
+        }
+        false
+    }
+
+    fn insert(&mut self, key: String, signature: String, breaking_if_added: Option<&'static str>) {
+        self.api.insert(
+            key,
+            Item {
+                signature,
+                breaking_if_added,
+            },
+        );
+    }
+
+    /// 收集 source_file 或 mod 的 declaration_list 中的公开项，prefix 为模块路径
+    fn items(&mut self, parent: Node, prefix: &str) {
+        let mut cursor = parent.walk();
+        for node in parent.named_children(&mut cursor) {
+            let kind = node.kind();
+            if kind == "impl_item" {
+                self.impl_item(node, prefix);
+                continue;
+            }
+            if !self.is_pub(node) {
+                continue;
+            }
+            let Some(name) = self.field(node, "name") else {
+                continue;
+            };
+            let path = format!("{}{}", prefix, name);
+            match kind {
+                "function_item" => self.insert(format!("fn {}", path), self.signature(node), None),
+                "struct_item" => self.struct_item(node, &path),
+                "enum_item" => self.enum_item(node, &path),
+                "trait_item" => self.trait_item(node, &path),
+                "type_item" | "const_item" | "static_item" => {
+                    let keyword = kind.trim_end_matches("_item");
+                    self.insert(format!("{} {}", keyword, path), self.signature(node), None);
+                }
+                "mod_item" => {
+                    self.insert(format!("mod {}", path), String::new(), None);
+                    if let Some(body) = node.child_by_field_name("body") {
+                        self.items(body, &format!("{}::", path));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn struct_item(&mut self, node: Node, path: &str) {
+        self.insert(format!("struct {}", path), self.signature(node), None);
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut cursor = body.walk();
+        match body.kind() {
+            "field_declaration_list" => {
+                for field in body.named_children(&mut cursor) {
+                    if field.kind() == "field_declaration"
+                        && self.is_pub(field)
+                        && let (Some(name), Some(ty)) =
+                            (self.field(field, "name"), self.field(field, "type"))
+                    {
+                        self.insert(format!("field {}.{}", path, name), normalize(ty), None);
+                    }
+                }
+            }
+            // 元组结构体：`pub struct Id(pub u64);`
+            "ordered_field_declaration_list" => {
+                let mut index = 0;
+                let mut public = false;
+                for child in body.named_children(&mut cursor) {
+                    if child.kind() == "visibility_modifier" {
+                        public = self.text(child) == "pub";
+                    } else if child.kind() != "attribute_item" {
+                        if public {
+                            self.insert(
+                                format!("field {}.{}", path, index),
+                                normalize(self.text(child)),
+                                None,
+                            );
+                        }
+                        index += 1;
+                        public = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn enum_item(&mut self, node: Node, path: &str) {
+        self.insert(format!("enum {}", path), self.signature(node), None);
+        // 穷尽枚举新增变体会让下游的 match 无法编译
+        let added = (!self.is_non_exhaustive(node)).then_some("exhaustive enum");
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut cursor = body.walk();
+        for variant in body.named_children(&mut cursor) {
+            if variant.kind() == "enum_variant"
+                && let Some(name) = self.field(variant, "name")
+            {
+                let signature = normalize(self.text(variant));
+                self.insert(format!("variant {}::{}", path, name), signature, added);
+            }
+        }
+    }
+
+    fn trait_item(&mut self, node: Node, path: &str) {
+        self.insert(format!("trait {}", path), self.signature(node), None);
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut cursor = body.walk();
+        for item in body.named_children(&mut cursor) {
+            // 没有默认实现的方法新增后，所有实现者都必须跟着实现
+            let added = match item.kind() {
+                "function_signature_item" => Some("required trait method"),
+                "function_item" => None,
+                _ => continue,
+            };
+            if let Some(name) = self.field(item, "name") {
+                let signature = self.signature(item);
+                self.insert(format!("fn {}::{}", path, name), signature, added);
+            }
+        }
+    }
+
+    /// 固有 impl 中的公开方法。trait 实现的方法签名由 trait 决定，不单独比较
+    fn impl_item(&mut self, node: Node, prefix: &str) {
+        if node.child_by_field_name("trait").is_some() {
+            return;
+        }
+        let Some(ty) = self.field(node, "type") else {
+            return;
+        };
+        // 去掉泛型参数，`Parser<'a>` 与 `Parser<T>` 视为同一类型
+        let ty = ty.split('<').next().unwrap_or(ty).trim().to_string();
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        let mut cursor = body.walk();
+        for item in body.named_children(&mut cursor) {
+            if item.kind() == "function_item"
+                && self.is_pub(item)
+                && let Some(name) = self.field(item, "name")
+            {
+                let signature = self.signature(item);
+                self.insert(format!("fn {}{}::{}", prefix, ty, name), signature, None);
+            }
+        }
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(';')
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(files: &[(&str, &str)]) -> LibraryFiles<impl Fn(&str) -> Option<String>> {
+        let files: HashMap<String, String> = files
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect();
+        LibraryFiles::new(move |path: &str| files.get(path).cloned())
+    }
+
+    #[test]
+    fn follows_pub_mod_from_lib_rs() {
+        let mut library = library(&[
+            (
+                "src/lib.rs",
+                "pub mod api;\nmod internal;\npub(crate) mod util;\npub mod nested { pub mod deep; }\n",
+            ),
+            ("src/api.rs", "pub mod v1;\n"),
+            ("src/api/v1/mod.rs", "pub fn get() {}\n"),
+            ("src/internal.rs", "pub mod leaked;\n"),
+            ("src/internal/leaked.rs", "pub fn f() {}\n"),
+            ("src/util.rs", "pub fn g() {}\n"),
+            ("src/nested/deep.rs", "pub fn h() {}\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("crates/cli/src/main.rs", "pub mod args;\n"),
+            ("crates/cli/src/args.rs", "pub struct Args;\n"),
+        ]);
+        for path in [
+            "src/lib.rs",
+            "src/api.rs",
+            "src/api/v1/mod.rs",
+            "src/nested/deep.rs",
+        ] {
+            assert!(library.contains(path), "{}", path);
+        }
+        for path in [
+            "src/internal.rs",
+            "src/internal/leaked.rs",
+            "src/util.rs",
+            "src/main.rs",
+            "src/bin/tool.rs",
+            "tests/it.rs",
+            "crates/cli/src/args.rs",
+            "README.md",
+        ] {
+            assert!(!library.contains(path), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// 摘要中最多列出的键变更条数
+const MAX_ENTRIES: usize = 100;
+/// 新增/删除的对象或数组按紧凑 JSON 展示时的最大长度
+const MAX_VALUE_LEN: usize = 80;
+
+/// 支持按键路径比较的结构化配置格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl StructuredFormat {
+    /// 按扩展名识别结构化配置文件
+    pub fn detect(path: &str) -> Option<Self> {
+        let ext = path.rsplit_once('.')?.1.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// 解析为统一的 JSON 值，便于按键路径比较
+    fn parse(self, content: &str) -> Option<Value> {
+        match self {
+            Self::Json => serde_json::from_str(content).ok(),
+            Self::Toml => toml::from_str::<toml::Value>(content).ok().map(from_toml),
+            Self::Yaml => parse_yaml(content),
+        }
+    }
+}
+
+/// 比较结构化文件新旧两个版本，生成键路径级别的变更列表，如
+/// `changed server.port: 8080 -> 9090`。任一版本解析失败时返回 None，
+/// 由调用方保留原始 diff
+pub fn summarize(format: StructuredFormat, old: &str, new: &str) -> Option<String> {
+    let old = format.parse(old)?;
+    let new = format.parse(new)?;
+
+    let mut changes = Vec::new();
+    compare(&mut String::new(), &old, &new, &mut changes);
+    if changes.is_empty() {
+        return Some("formatting only, no value changes".into());
+    }
+
+    let total = changes.len();
+    changes.truncate(MAX_ENTRIES);
+    let mut summary = String::from("key changes:");
+    for change in &changes {
+        summary.push_str("\n  ");
+        summary.push_str(change);
+    }
+    if total > MAX_ENTRIES {
+        summary.push_str(&format!("\n  ... and {} more", total - MAX_ENTRIES));
+    }
+    Some(summary)
+}
+
+/// 递归比较两个值，path 为当前键路径
+fn compare(path: &mut String, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let len = push_key(path, key);
+                match new_map.get(key) {
+                    Some(new_value) => compare(path, old_value, new_value, changes),
+                    None => changes.push(format!("removed {}: {}", path, display(old_value))),
+                }
+                path.truncate(len);
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    let len = push_key(path, key);
+                    changes.push(format!("added {}: {}", path, display(new_value)));
+                    path.truncate(len);
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for i in 0..old_items.len().max(new_items.len()) {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                match (old_items.get(i), new_items.get(i)) {
+                    (Some(o), Some(n)) => compare(path, o, n, changes),
+                    (Some(o), None) => changes.push(format!("removed {}: {}", path, display(o))),
+                    (None, Some(n)) => changes.push(format!("added {}: {}", path, display(n))),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        _ if old != new => {
+            let path = if path.is_empty() { "(root)" } else { path };
+            changes.push(format!(
+                "changed {}: {} -> {}",
+                path,
+                display(old),
+                display(new)
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// 在路径后追加一级键，返回追加前的长度以便回退。
+/// 含有分隔符或空白的键加引号
+fn push_key(path: &mut String, key: &str) -> usize {
+    let len = path.len();
+    if !path.is_empty() {
+        path.push('.');
+    }
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/' | '@' | '$'));
+    if plain {
+        path.push_str(key);
+    } else {
+        path.push_str(&serde_json::to_string(key).unwrap_or_default());
+    }
+    len
+}
+
+/// 以紧凑 JSON 形式展示值，过长时截断
+fn display(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() <= MAX_VALUE_LEN {
+        return text;
+    }
+    let cut: String = text.chars().take(MAX_VALUE_LEN).collect();
+    format!("{}...", cut)
+}
+
+/// YAML 可能包含多个文档（以 `---` 分隔），多文档时按数组处理
+fn parse_yaml(content: &str) -> Option<Value> {
+    let mut docs = serde_yaml::Deserializer::from_str(content)
+        .map(Value::deserialize)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    match docs.len() {
+        0 => Some(Value::Null),
+        1 => docs.pop(),
+        _ => Some(Value::Array(docs)),
+    }
+}
+
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, from_toml(value)))
+                .collect(),
+        ),
+    }
+}
@@ -0,0 +1,266 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use git2::{Commit, Oid, Repository, Signature, Tree};
+
+use crate::error::AppError;
+
+/// 按 `commit.gpgsign` / `gpg.format` 配置对提交签名的签名程序。
+pub enum Signer {
+    /// openpgp 与 x509 格式：`gpg` / `gpgsm` 读取标准输入，签名输出到标准输出
+    Gpg { program: String, key: String },
+    /// ssh 格式：`ssh-keygen -Y sign` 签名文件，签名写到 `<文件>.sig`
+    Ssh { program: String, key: String },
+}
+
+impl Signer {
+    /// 读取仓库的签名配置，未开启 `commit.gpgsign` 时返回 None
+    pub fn from_config(repo: &Repository) -> Result<Option<Self>, AppError> {
+        let config = repo
+            .config()
+            .and_then(|mut c| c.snapshot())
+            .map_err(|e| AppError::Git(format!("无法读取 Git 配置: {}", e)))?;
+        if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return Ok(None);
+        }
+        let get = |key: &str| config.get_string(key).ok().filter(|v| !v.is_empty());
+        let format = get("gpg.format").unwrap_or_else(|| "openpgp".into());
+        let key = get("user.signingkey");
+
+        let signer = match format.as_str() {
+            "ssh" => Signer::Ssh {
+                program: get("gpg.ssh.program").unwrap_or_else(|| "ssh-keygen".into()),
+                key: key.ok_or_else(|| {
+                    AppError::Git("gpg.format 为 ssh 时需要配置 user.signingkey".into())
+                })?,
+            },
+            "openpgp" | "x509" => {
+                let program = if format == "x509" {
+                    get("gpg.x509.program").unwrap_or_else(|| "gpgsm".into())
+                } else {
+                    get("gpg.openpgp.program")
+                        .or_else(|| get("gpg.program"))
+                        .unwrap_or_else(|| "gpg".into())
+                };
+                // 与 git 一致：未配置签名密钥时按提交者身份查找
+                let key = match key {
+                    Some(key) => key,
+                    None => {
+                        let sig = repo
+                            .signature()
+                            .map_err(|e| AppError::Git(format!("无法获取签名信息: {}", e)))?;
+                        format!(
+                            "{} <{}>",
+                            sig.name().unwrap_or_default(),
+                            sig.email().unwrap_or_default()
+                        )
+                    }
+                };
+                Signer::Gpg { program, key }
+            }
+            other => {
+                return Err(AppError::Git(format!(
+                    "不支持的签名格式 gpg.format={}",
+                    other
+                )));
+            }
+        };
+        Ok(Some(signer))
+    }
+
+    /// 对提交对象的内容签名，返回 ASCII armor 格式的签名
+    pub fn sign(&self, buffer: &str) -> Result<String, AppError> {
+        match self {
+            Signer::Gpg { program, key } => sign_gpg(program, key, buffer),
+            Signer::Ssh { program, key } => sign_ssh(program, key, buffer),
+        }
+    }
+}
+
+/// 创建提交对象（不移动任何引用）。signer 不为 None 时先对提交内容签名，再以签名提交写入
+pub fn create_commit(
+    repo: &Repository,
+    signer: Option<&Signer>,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[&Commit],
+) -> Result<Oid, AppError> {
+    match signer {
+        Some(signer) => {
+            let buffer = repo
+                .commit_create_buffer(author, committer, message, tree, parents)
+                .map_err(|e| AppError::Git(format!("无法生成提交内容: {}", e)))?;
+            let buffer = buffer
+                .as_str()
+                .ok_or_else(|| AppError::Git("提交内容不是有效的 UTF-8".into()))?;
+            let signature = signer.sign(buffer)?;
+            repo.commit_signed(buffer, &signature, None)
+        }
+        None => repo.commit(None, author, committer, message, tree, parents),
+    }
+    .map_err(|e| AppError::Git(format!("提交失败: {}", e)))
+}
+
+fn sign_gpg(program: &str, key: &str, buffer: &str) -> Result<String, AppError> {
+    let mut child = Command::new(program)
+        .args(["--status-fd=2", "-bsau", key])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Git(format!("无法执行签名程序 {}: {}", program, e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(buffer.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    // 退出码为 0 但没有 SIG_CREATED 状态时同样视为失败，与 git 一致
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success()
+        || !stderr
+            .lines()
+            .any(|line| line.starts_with("[GNUPG:] SIG_CREATED "))
+    {
+        return Err(sign_failed(program, &stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn sign_ssh(program: &str, key: &str, buffer: &str) -> Result<String, AppError> {
+    // ssh-keygen 把签名写到 `<文件>.sig`，因此所有文件都放在只有当前用户可访问、
+    // 名称随机的临时目录中，避免其他用户抢先创建或替换签名文件；目录在返回时删除
+    let dir = tempfile::Builder::new().prefix("llmc-signing-").tempdir()?;
+    let buffer_file = dir.path().join("commit");
+    let signature_file = dir.path().join("commit.sig");
+    fs::write(&buffer_file, buffer)?;
+
+    let mut command = Command::new(program);
+    command.args(["-Y", "sign", "-n", "git", "-f"]);
+    // user.signingkey 可以直接写公钥（`key::ssh-ed25519 ...` 或 `ssh-ed25519 ...`），
+    // 此时私钥由 ssh-agent 提供
+    match key
+        .strip_prefix("key::")
+        .or_else(|| key.starts_with("ssh-").then_some(key))
+    {
+        Some(public_key) => {
+            let key_file = dir.path().join("key.pub");
+            fs::write(&key_file, public_key)?;
+            command.arg(key_file).arg("-U");
+        }
+        None => {
+            command.arg(expand_home(key));
+        }
+    }
+    let output = command
+        .arg(&buffer_file)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| AppError::Git(format!("无法执行签名程序 {}: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(sign_failed(
+            program,
+            &String::from_utf8_lossy(&output.stderr),
+        ));
+    }
+    Ok(fs::read_to_string(&signature_file)?)
+}
+
+fn sign_failed(program: &str, stderr: &str) -> AppError {
+    // 不把 gpg 的状态行展示给用户
+    let detail: Vec<&str> = stderr
+        .lines()
+        .filter(|line| !line.starts_with("[GNUPG:]"))
+        .collect();
+    AppError::Git(format!(
+        "使用 {} 签名提交失败: {}",
+        program,
+        detail.join("\n").trim()
+    ))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 用临时生成的 ed25519 密钥签名提交，再用 ssh-keygen -Y verify 校验 gpgsig 头
+    #[test]
+    fn signs_commit_with_ssh_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("id_ed25519");
+        let keygen = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "llmc-test", "-f"])
+            .arg(&key)
+            .status();
+        if !keygen.is_ok_and(|s| s.success()) {
+            eprintln!("ssh-keygen 不可用，跳过");
+            return;
+        }
+
+        let repo = Repository::init(dir.path().join("repo")).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Tester").unwrap();
+        config.set_str("user.email", "tester@example.com").unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("gpg.format", "ssh").unwrap();
+        config
+            .set_str(
+                "user.signingkey",
+                key.with_extension("pub").to_str().unwrap(),
+            )
+            .unwrap();
+
+        let signer = Signer::from_config(&repo).unwrap();
+        assert!(matches!(signer, Some(Signer::Ssh { .. })));
+        let sig = repo.signature().unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let id = create_commit(&repo, signer.as_ref(), &sig, &sig, "init\n", &tree, &[]).unwrap();
+
+        let (signature, signed_data) = repo.extract_signature(&id, None).unwrap();
+        let signature = signature.as_str().unwrap();
+        assert!(signature.starts_with("-----BEGIN SSH SIGNATURE-----"));
+
+        let public_key = fs::read_to_string(key.with_extension("pub")).unwrap();
+        let allowed = dir.path().join("allowed_signers");
+        fs::write(&allowed, format!("tester@example.com {}", public_key)).unwrap();
+        let signature_file = dir.path().join("commit.sig");
+        fs::write(&signature_file, signature).unwrap();
+
+        let mut verify = Command::new("ssh-keygen")
+            .args([
+                "-Y",
+                "verify",
+                "-n",
+                "git",
+                "-I",
+                "tester@example.com",
+                "-f",
+            ])
+            .arg(&allowed)
+            .arg("-s")
+            .arg(&signature_file)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        verify
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(&signed_data)
+            .unwrap();
+        assert!(verify.wait().unwrap().success());
+    }
+}
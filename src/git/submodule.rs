@@ -0,0 +1,60 @@
+use git2::{Oid, Repository, Sort};
+
+/// 摘要中最多列出的提交数
+const MAX_COMMITS: usize = 30;
+
+/// gitlink（子模块指针）的文件模式
+pub const GITLINK_MODE: u32 = 0o160000;
+
+/// 描述子模块指针的变化：打开子模块仓库，列出新旧指针之间的提交标题。
+/// 子模块未检出或找不到对应提交时，只给出新旧 SHA
+pub fn summarize(repo: &Repository, path: &str, old: Oid, new: Oid) -> String {
+    let range = format!("{}..{}", short(old), short(new));
+    if old.is_zero() {
+        return format!("submodule added at {}", short(new));
+    }
+    if new.is_zero() {
+        return format!("submodule removed (was at {})", short(old));
+    }
+
+    let Some(sub) = repo.find_submodule(path).ok().and_then(|s| s.open().ok()) else {
+        return format!("submodule updated {} (not checked out)", range);
+    };
+
+    // 指针前移时列出新增的提交，回退时列出被撤回的提交
+    let (verb, subjects) = match commit_subjects(&sub, old, new) {
+        Some(subjects) if !subjects.is_empty() => ("brings in", subjects),
+        _ => match commit_subjects(&sub, new, old) {
+            Some(subjects) if !subjects.is_empty() => ("rewinds", subjects),
+            _ => return format!("submodule updated {} (commits unavailable)", range),
+        },
+    };
+
+    let total = subjects.len();
+    let mut summary = format!("submodule updated {}, {} {} commit(s):", range, verb, total);
+    for subject in subjects.iter().take(MAX_COMMITS) {
+        summary.push_str("\n  ");
+        summary.push_str(subject);
+    }
+    if total > MAX_COMMITS {
+        summary.push_str(&format!("\n  ... and {} more", total - MAX_COMMITS));
+    }
+    summary
+}
+
+/// from（不含）到 to（含）之间的提交标题，按时间从旧到新排列
+fn commit_subjects(repo: &Repository, from: Oid, to: Oid) -> Option<Vec<String>> {
+    let mut walk = repo.revwalk().ok()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE).ok()?;
+    walk.push(to).ok()?;
+    walk.hide(from).ok()?;
+    walk.map(|id| {
+        let commit = repo.find_commit(id.ok()?).ok()?;
+        Some(commit.summary().unwrap_or_default().to_string())
+    })
+    .collect()
+}
+
+fn short(id: Oid) -> String {
+    id.to_string()[..7].to_string()
+}
@@ -0,0 +1,220 @@
+use tree_sitter::{Node, Parser, Point, Tree};
+
+use super::{FileDiff, LineKind, Symbol};
+
+/// 支持解析符号的语言。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    Python,
+    TypeScript,
+    Tsx,
+    Go,
+}
+
+impl Language {
+    fn detect(path: &str) -> Option<Self> {
+        let ext = path.rsplit_once('.')?.1.to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "ts" | "mts" | "cts" | "js" | "mjs" | "cjs" => Some(Self::TypeScript),
+            "tsx" | "jsx" => Some(Self::Tsx),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    fn parse(self, source: &str) -> Option<Tree> {
+        let language = match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE,
+            Self::Python => tree_sitter_python::LANGUAGE,
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX,
+            Self::Go => tree_sitter_go::LANGUAGE,
+        };
+        let mut parser = Parser::new();
+        parser.set_language(&language.into()).ok()?;
+        parser.parse(source, None)
+    }
+
+    /// 将语法节点描述为符号，如 `fn parse_header`、`impl Display for Header`。
+    /// 第二个值表示该符号是否为函数（可以整体附加到 prompt）
+    fn describe(self, node: Node, source: &str) -> Option<(String, bool)> {
+        let text = |field: &str| {
+            node.child_by_field_name(field)
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        };
+        let named = |kind: &str, is_function: bool| {
+            text("name").map(|name| (format!("{} {}", kind, name), is_function))
+        };
+        match (self, node.kind()) {
+            (Self::Rust, "function_item") => named("fn", true),
+            (Self::Rust, "impl_item") => {
+                let ty = text("type")?;
+                Some(match text("trait") {
+                    Some(tr) => (format!("impl {} for {}", tr, ty), false),
+                    None => (format!("impl {}", ty), false),
+                })
+            }
+            (Self::Rust, "trait_item") => named("trait", false),
+            (Self::Rust, "struct_item") => named("struct", false),
+            (Self::Rust, "enum_item") => named("enum", false),
+            (Self::Rust, "mod_item") => named("mod", false),
+            (Self::Rust, "macro_definition") => named("macro_rules!", true),
+            (Self::Python, "function_definition") => named("def", true),
+            (Self::Python, "class_definition") => named("class", false),
+            (
+                Self::TypeScript | Self::Tsx,
+                "function_declaration" | "generator_function_declaration",
+            ) => named("function", true),
+            (Self::TypeScript | Self::Tsx, "method_definition") => named("method", true),
+            (Self::TypeScript | Self::Tsx, "class_declaration" | "abstract_class_declaration") => {
+                named("class", false)
+            }
+            (Self::TypeScript | Self::Tsx, "interface_declaration") => named("interface", false),
+            (Self::TypeScript | Self::Tsx, "enum_declaration") => named("enum", false),
+            (Self::TypeScript | Self::Tsx, "type_alias_declaration") => named("type", false),
+            // const handler = () => {...}
+            (Self::TypeScript | Self::Tsx, "variable_declarator") => {
+                let value = node.child_by_field_name("value")?;
+                matches!(value.kind(), "arrow_function" | "function_expression")
+                    .then(|| named("function", true))
+                    .flatten()
+            }
+            (Self::Go, "function_declaration") => named("func", true),
+            (Self::Go, "method_declaration") => {
+                let receiver = text("receiver")?;
+                Some((format!("func {} {}", receiver, text("name")?), true))
+            }
+            (Self::Go, "type_spec") => named("type", false),
+            _ => None,
+        }
+    }
+}
+
+/// 一个文件的某个版本及其语法树。
+struct ParsedSource<'a> {
+    language: Language,
+    source: &'a str,
+    lines: Vec<&'a str>,
+    tree: Tree,
+}
+
+impl<'a> ParsedSource<'a> {
+    fn new(language: Language, source: Option<&'a str>) -> Option<Self> {
+        let source = source?;
+        Some(Self {
+            language,
+            source,
+            lines: source.lines().collect(),
+            tree: language.parse(source)?,
+        })
+    }
+
+    /// 包含第 line 行（从 1 开始）的符号，由外到内排列，如 `impl Parser > fn parse_header`。
+    /// 同时返回最内层函数的完整源码
+    fn enclosing(&self, line: u32) -> Option<(String, Option<&'a str>)> {
+        let row = line.checked_sub(1)? as usize;
+        let text = self.lines.get(row)?;
+        // 定位到该行第一个非空白字符，避免落在上一个节点的尾部
+        let column = text.len() - text.trim_start().len();
+        let point = Point::new(row, column);
+        let mut node = self
+            .tree
+            .root_node()
+            .descendant_for_point_range(point, point)?;
+
+        let mut path = Vec::new();
+        let mut function = None;
+        loop {
+            if let Some((name, is_function)) = self.language.describe(node, self.source) {
+                if is_function && function.is_none() {
+                    // 从所在行行首截取，保留第一行的缩进
+                    let range = node.byte_range();
+                    let line_start = self.source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+                    let start = if self.source[line_start..range.start].trim().is_empty() {
+                        line_start
+                    } else {
+                        range.start
+                    };
+                    function = self.source.get(start..range.end);
+                }
+                path.push(name);
+            }
+            match node.parent() {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+        if path.is_empty() {
+            return None;
+        }
+        path.reverse();
+        Some((path.join(" > "), function))
+    }
+}
+
+/// 解析文件新旧两个版本，为每个 hunk 标注所在的函数、impl 或类，
+/// 并收集文件中被修改的符号。不支持的语言或解析失败时保持原样
+pub fn annotate(file: &mut FileDiff, old: Option<&str>, new: Option<&str>) {
+    let Some(language) = Language::detect(file.path()) else {
+        return;
+    };
+    let old = ParsedSource::new(language, old);
+    let new = ParsedSource::new(language, new);
+
+    // 第二个值记录源码是否取自新版本
+    let mut symbols: Vec<(Symbol, bool)> = Vec::new();
+    for hunk in &mut file.hunks {
+        let (mut old_line, mut new_line) = (hunk.old_start, hunk.new_start);
+        let mut first = None;
+        for line in &hunk.lines {
+            // 新增和上下文行按新版本定位，删除行按旧版本定位
+            let found = match line.kind {
+                LineKind::Added => new.as_ref().and_then(|s| s.enclosing(new_line)),
+                LineKind::Removed => old.as_ref().and_then(|s| s.enclosing(old_line)),
+                _ => None,
+            };
+            match line.kind {
+                LineKind::Added => new_line += 1,
+                LineKind::Removed => old_line += 1,
+                LineKind::Context => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                LineKind::NoNewlineAtEof => {}
+            }
+            let Some((name, source)) = found else {
+                continue;
+            };
+            if first.is_none() {
+                first = Some(name.clone());
+            }
+            // 函数源码优先取新版本
+            let prefer = line.kind == LineKind::Added;
+            match symbols.iter_mut().find(|(s, _)| s.name == name) {
+                Some((symbol, from_new)) if symbol.source.is_none() || (prefer && !*from_new) => {
+                    symbol.source = source.map(str::to_string);
+                    *from_new = prefer;
+                }
+                Some(_) => {}
+                None => symbols.push((
+                    Symbol {
+                        name,
+                        source: source.map(str::to_string),
+                    },
+                    prefer,
+                )),
+            }
+        }
+
+        // 用解析出的符号替换 git 按正则猜测的 hunk 上下文
+        if let Some(name) = first
+            && let Some(end) = hunk.header.get(2..).and_then(|h| h.find("@@"))
+        {
+            hunk.header = format!("{} {}", &hunk.header[..end + 4], name);
+        }
+    }
+    file.symbols = symbols.into_iter().map(|(symbol, _)| symbol).collect();
+}
@@ -1,6 +1,7 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use super::DiffResult;
+use super::{CommitInfo, DiffResult, DiffScope, TempIndex};
 use crate::error::AppError;
 
 /// Git 操作的抽象接口。
@@ -9,9 +10,73 @@ pub trait GitOperations {
     /// 检测当前目录是否为 Git 仓库，返回仓库根路径
     fn discover_repo(&self, path: &Path) -> Result<PathBuf, AppError>;
 
-    /// 获取暂存区的 diff 内容
-    fn staged_diff(&self, repo_path: &Path) -> Result<DiffResult, AppError>;
+    /// 按指定范围获取 diff 内容。index 为 stage_all 返回的临时 index 时，
+    /// `DiffScope::All` 比较 HEAD 与临时 index
+    fn diff(
+        &self,
+        repo_path: &Path,
+        scope: DiffScope,
+        index: Option<&TempIndex>,
+    ) -> Result<DiffResult, AppError>;
 
-    /// 使用指定的 commit message 提交暂存区的变更
-    fn commit(&self, repo_path: &Path, message: &str) -> Result<(), AppError>;
+    /// 获取指定提交相对其父提交的 diff
+    fn commit_diff(&self, repo_path: &Path, id: &str) -> Result<DiffResult, AppError>;
+
+    /// 把所有已跟踪文件的修改暂存到临时 index，include_untracked 为 true 时同时暂存未跟踪文件。
+    /// 暂存区在提交前保持不变
+    fn stage_all(&self, repo_path: &Path, include_untracked: bool) -> Result<TempIndex, AppError>;
+
+    /// 执行仓库的 pre-commit 钩子，钩子失败时返回错误。
+    /// 在获取 diff 之前调用，格式化类钩子重新暂存的文件会反映在生成 message 所用的 diff 中；
+    /// index 不为 None 时钩子通过 GIT_INDEX_FILE 使用临时 index
+    fn run_pre_commit(&self, repo_path: &Path, index: Option<&TempIndex>) -> Result<(), AppError>;
+
+    /// 使用指定的 commit message 提交暂存区（index 不为 None 时为临时 index）的变更，
+    /// 并执行 prepare-commit-msg、commit-msg 和 post-commit 钩子；no_verify 为 true 时跳过 commit-msg 钩子。
+    /// 使用临时 index 时提交成功后用它替换暂存区
+    fn commit(
+        &self,
+        repo_path: &Path,
+        message: &str,
+        no_verify: bool,
+        index: Option<TempIndex>,
+    ) -> Result<(), AppError>;
+
+    /// 安装调用 llmc 的 prepare-commit-msg 钩子，command 为钩子中执行的 llmc 命令，
+    /// 已有其他钩子时需要 force 才会覆盖。返回钩子文件路径
+    fn install_hook(
+        &self,
+        repo_path: &Path,
+        command: &str,
+        force: bool,
+    ) -> Result<PathBuf, AppError>;
+
+    /// 删除 llmc 安装的 prepare-commit-msg 钩子，返回钩子文件路径
+    fn uninstall_hook(&self, repo_path: &Path) -> Result<PathBuf, AppError>;
+
+    /// 获取 HEAD 提交的 message
+    fn head_message(&self, repo_path: &Path) -> Result<String, AppError>;
+
+    /// 用暂存区和指定的 commit message 重写 HEAD 提交，钩子的处理同 commit
+    fn amend(&self, repo_path: &Path, message: &str, no_verify: bool) -> Result<(), AppError>;
+
+    /// 工作区和暂存区是否没有未提交的修改
+    fn is_clean(&self, repo_path: &Path) -> Result<bool, AppError>;
+
+    /// 按从旧到新的顺序列出 `<base>..<head>` 范围内的提交
+    fn commits_in_range(&self, repo_path: &Path, range: &str) -> Result<Vec<CommitInfo>, AppError>;
+
+    /// 把当前 HEAD 保存为备份引用，返回引用名称
+    fn backup_head(&self, repo_path: &Path) -> Result<String, AppError>;
+
+    /// 删除 backup_head 创建的备份引用
+    fn delete_backup(&self, repo_path: &Path, backup: &str) -> Result<(), AppError>;
+
+    /// 用新的 message 重放范围内的提交（key 为提交 id）
+    fn rewrite_messages(
+        &self,
+        repo_path: &Path,
+        commits: &[CommitInfo],
+        messages: &HashMap<String, String>,
+    ) -> Result<(), AppError>;
 }
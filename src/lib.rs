@@ -5,3 +5,4 @@ pub mod error;
 pub mod git;
 pub mod llm;
 pub mod prompt;
+pub mod tokenizer;
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::error::AppError;
+
+/// 根据 HTTP 状态码和响应体，将后端错误解析为具体的 AppError。
+///
+/// 兼容以下几种错误体格式：
+/// - OpenAI：`{"error": {"message": "...", "code": "context_length_exceeded"}}`
+/// - Anthropic：`{"type": "error", "error": {"type": "rate_limit_error", "message": "..."}}`
+/// - Ollama：`{"error": "..."}`
+pub fn api_error(status: StatusCode, retry_after: Option<Duration>, body: &str) -> AppError {
+    let json: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+    let error = &json["error"];
+    let code = error["code"]
+        .as_str()
+        .or_else(|| error["type"].as_str())
+        .unwrap_or_default();
+    let detail = error["message"]
+        .as_str()
+        .or_else(|| error.as_str())
+        .unwrap_or(body);
+    let message = format!("API 返回错误 ({}): {}", status, detail);
+
+    if is_context_overflow(status, code, detail) {
+        return AppError::ContextLengthExceeded {
+            max: parse_context_max(detail),
+            message,
+        };
+    }
+
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => AppError::Unauthorized(message),
+        StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited {
+            retry_after,
+            message,
+        },
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => AppError::Timeout(message),
+        s if s.is_server_error() => AppError::ServiceUnavailable(message),
+        _ => match code {
+            "invalid_api_key" | "authentication_error" | "permission_error" => {
+                AppError::Unauthorized(message)
+            }
+            "rate_limit_exceeded" | "rate_limit_error" => AppError::RateLimited {
+                retry_after,
+                message,
+            },
+            "overloaded_error" => AppError::ServiceUnavailable(message),
+            _ => AppError::Llm(message),
+        },
+    }
+}
+
+/// 将 reqwest 的传输层错误解析为具体的 AppError
+pub fn transport_error(e: &reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::Timeout(format!("请求超时: {}", e))
+    } else if e.is_decode() {
+        AppError::InvalidResponse(format!("响应解析失败: {}", e))
+    } else {
+        AppError::ServiceUnavailable(format!("请求发送失败: {}", e))
+    }
+}
+
+fn is_context_overflow(status: StatusCode, code: &str, detail: &str) -> bool {
+    if status == StatusCode::PAYLOAD_TOO_LARGE || code == "context_length_exceeded" {
+        return true;
+    }
+    let detail = detail.to_lowercase();
+    status == StatusCode::BAD_REQUEST
+        && (detail.contains("maximum context length")
+            || detail.contains("prompt is too long")
+            || detail.contains("context window"))
+}
+
+/// 从错误信息中提取上下文上限，例如
+/// "This model's maximum context length is 8192 tokens" 或
+/// "prompt is too long: 210000 tokens > 200000 maximum"
+fn parse_context_max(detail: &str) -> Option<usize> {
+    let lower = detail.to_lowercase();
+    if let Some(pos) = lower.find("maximum context length is") {
+        return first_number(&lower[pos..]);
+    }
+    if let Some(pos) = lower.find(" maximum") {
+        return lower[..pos]
+            .rsplit(|c: char| !c.is_ascii_digit())
+            .find(|s| !s.is_empty())
+            .and_then(|s| s.parse().ok());
+    }
+    None
+}
+
+fn first_number(s: &str) -> Option<usize> {
+    s.split(|c: char| !c.is_ascii_digit())
+        .find(|s| !s.is_empty())
+        .and_then(|s| s.parse().ok())
+}
@@ -1,4 +1,4 @@
-use super::providers::OpenAiProvider;
+use super::providers::{AnthropicProvider, OllamaProvider, OpenAiProvider};
 use super::traits::LlmProvider;
 use crate::config::ModelConfig;
 use crate::error::AppError;
@@ -10,8 +10,9 @@ impl LlmProviderFactory {
     pub fn create(model_config: &ModelConfig) -> Result<Box<dyn LlmProvider>, AppError> {
         match model_config.provider.as_str() {
             "openai" => Ok(Box::new(OpenAiProvider::new(model_config)?)),
+            "anthropic" => Ok(Box::new(AnthropicProvider::new(model_config)?)),
+            "ollama" => Ok(Box::new(OllamaProvider::new(model_config)?)),
             // 扩展点：新增后端在此注册
-            // "anthropic" => Ok(Box::new(AnthropicProvider::new(model_config)?)),
             other => Err(AppError::Config(format!("未知的 provider 类型: {}", other))),
         }
     }
@@ -1,10 +1,16 @@
+mod api_error;
 mod factory;
 pub mod providers;
 mod request;
 mod response;
+mod retry;
+mod sse;
 mod traits;
 
+pub use api_error::{api_error, transport_error};
 pub use factory::LlmProviderFactory;
 pub use request::LlmRequest;
 pub use response::LlmResponse;
-pub use traits::LlmProvider;
+pub use retry::send_with_retry;
+pub use sse::SseDecoder;
+pub use traits::{DeltaHandler, LlmProvider};
@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+use crate::config::{ModelConfig, RetryConfig};
+use crate::error::AppError;
+use crate::llm::{LlmProvider, LlmRequest, LlmResponse, send_with_retry};
+
+/// Anthropic Messages API 版本号
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Anthropic 原生 Messages API 的 LLM Provider 实现。
+pub struct AnthropicProvider {
+    client: Client,
+    api_base: String,
+    api_key: String,
+    model_id: String,
+    max_tokens: Option<usize>,
+    retry: RetryConfig,
+}
+
+impl AnthropicProvider {
+    pub fn new(config: &ModelConfig) -> Result<Self, AppError> {
+        let api_key = config.api_key()?;
+
+        Ok(Self {
+            client: Client::new(),
+            api_base: config.api_base.clone(),
+            api_key,
+            model_id: config.model_id.clone(),
+            max_tokens: config.max_tokens,
+            retry: config.retry.clone().unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, AppError> {
+        let url = format!("{}/v1/messages", self.api_base.trim_end_matches('/'));
+
+        // Messages API 要求必须提供 max_tokens
+        let max_tokens = request.max_tokens.or(self.max_tokens).unwrap_or(1024);
+
+        // system prompt 是顶层字段，而不是 messages 中的一条消息
+        let body = json!({
+            "model": self.model_id,
+            "system": request.system_prompt,
+            "messages": [
+                {
+                    "role": "user",
+                    "content": request.user_prompt
+                }
+            ],
+            "max_tokens": max_tokens
+        });
+
+        let resp = send_with_retry(&self.retry, || {
+            self.client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .header("Content-Type", "application/json")
+                .json(&body)
+        })
+        .await?;
+
+        let json: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AppError::InvalidResponse(format!("响应解析失败: {}", e)))?;
+
+        // 响应内容是 content block 数组，拼接所有 text 类型的 block
+        let blocks = json["content"]
+            .as_array()
+            .ok_or_else(|| AppError::InvalidResponse("无法从响应中提取 commit message".into()))?;
+        let commit_message = blocks
+            .iter()
+            .filter(|b| b["type"] == "text")
+            .filter_map(|b| b["text"].as_str())
+            .collect::<String>()
+            .trim()
+            .to_string();
+        if commit_message.is_empty() {
+            return Err(AppError::InvalidResponse(
+                "无法从响应中提取 commit message".into(),
+            ));
+        }
+
+        let input = json["usage"]["input_tokens"].as_u64();
+        let output = json["usage"]["output_tokens"].as_u64();
+        let usage_tokens = match (input, output) {
+            (None, None) => None,
+            (i, o) => Some((i.unwrap_or(0) + o.unwrap_or(0)) as usize),
+        };
+
+        Ok(LlmResponse {
+            commit_message,
+            usage_tokens,
+        })
+    }
+}
@@ -1,3 +1,7 @@
+mod anthropic;
+mod ollama;
 mod openai;
 
+pub use anthropic::AnthropicProvider;
+pub use ollama::OllamaProvider;
 pub use openai::OpenAiProvider;
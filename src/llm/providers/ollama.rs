@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+use crate::config::{ModelConfig, ModelOptions, RetryConfig};
+use crate::error::AppError;
+use crate::llm::{LlmProvider, LlmRequest, LlmResponse, send_with_retry};
+
+/// 本地 Ollama 守护进程的 LLM Provider 实现。
+/// 调用原生 `/api/chat` 接口，不需要 API Key，diff 不会离开本机。
+pub struct OllamaProvider {
+    client: Client,
+    api_base: String,
+    model_id: String,
+    max_tokens: Option<usize>,
+    options: ModelOptions,
+    retry: RetryConfig,
+}
+
+impl OllamaProvider {
+    pub fn new(config: &ModelConfig) -> Result<Self, AppError> {
+        Ok(Self {
+            client: Client::new(),
+            api_base: config.api_base.clone(),
+            model_id: config.model_id.clone(),
+            max_tokens: config.max_tokens,
+            options: config.options.clone().unwrap_or_default(),
+            retry: config.retry.clone().unwrap_or_default(),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.api_base.trim_end_matches('/'), path)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, AppError> {
+        let url = self.url("/api/chat");
+
+        // max_tokens 对应 Ollama 的 num_predict
+        let mut options = serde_json::Map::new();
+        if let Some(n) = request.max_tokens.or(self.max_tokens) {
+            options.insert("num_predict".into(), json!(n));
+        }
+        if let Some(n) = self.options.num_ctx {
+            options.insert("num_ctx".into(), json!(n));
+        }
+        if let Some(t) = self.options.temperature {
+            options.insert("temperature".into(), json!(t));
+        }
+
+        let body = json!({
+            "model": self.model_id,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": request.system_prompt
+                },
+                {
+                    "role": "user",
+                    "content": request.user_prompt
+                }
+            ],
+            "options": options,
+            "stream": false
+        });
+
+        let resp = send_with_retry(&self.retry, || self.client.post(&url).json(&body)).await?;
+
+        let json: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AppError::InvalidResponse(format!("响应解析失败: {}", e)))?;
+
+        let commit_message = json["message"]["content"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidResponse("无法从响应中提取 commit message".into()))?
+            .trim()
+            .to_string();
+
+        let prompt = json["prompt_eval_count"].as_u64();
+        let eval = json["eval_count"].as_u64();
+        let usage_tokens = match (prompt, eval) {
+            (None, None) => None,
+            (p, e) => Some((p.unwrap_or(0) + e.unwrap_or(0)) as usize),
+        };
+
+        Ok(LlmResponse {
+            commit_message,
+            usage_tokens,
+        })
+    }
+
+    async fn installed_models(&self) -> Result<Vec<String>, AppError> {
+        let url = self.url("/api/tags");
+        let resp = send_with_retry(&self.retry, || self.client.get(&url)).await?;
+
+        let json: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AppError::InvalidResponse(format!("响应解析失败: {}", e)))?;
+
+        Ok(json["models"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|m| m["name"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde_json::json;
 
-use crate::config::ModelConfig;
+use crate::config::{ModelConfig, RetryConfig};
 use crate::error::AppError;
-use crate::llm::{LlmProvider, LlmRequest, LlmResponse};
+use crate::llm::{
+    DeltaHandler, LlmProvider, LlmRequest, LlmResponse, SseDecoder, send_with_retry,
+    transport_error,
+};
 
 /// OpenAI 兼容的 LLM Provider 实现。
 /// 支持所有兼容 OpenAI Chat Completions API 的后端（OpenAI、DeepSeek 等）。
@@ -14,16 +17,13 @@ pub struct OpenAiProvider {
     api_key: String,
     model_id: String,
     max_tokens: Option<usize>,
+    stream: bool,
+    retry: RetryConfig,
 }
 
 impl OpenAiProvider {
     pub fn new(config: &ModelConfig) -> Result<Self, AppError> {
-        let api_key = std::env::var(&config.api_key_env).map_err(|_| {
-            AppError::Config(format!(
-                "环境变量 {} 未设置，请设置对应的 API Key",
-                config.api_key_env
-            ))
-        })?;
+        let api_key = config.api_key()?;
 
         Ok(Self {
             client: Client::new(),
@@ -31,22 +31,15 @@ impl OpenAiProvider {
             api_key,
             model_id: config.model_id.clone(),
             max_tokens: config.max_tokens,
+            stream: config.stream.unwrap_or(true),
+            retry: config.retry.clone().unwrap_or_default(),
         })
     }
-}
-
-#[async_trait]
-impl LlmProvider for OpenAiProvider {
-    fn name(&self) -> &str {
-        "openai"
-    }
-
-    async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, AppError> {
-        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
 
+    fn request_body(&self, request: &LlmRequest, stream: bool) -> serde_json::Value {
         let max_tokens = request.max_tokens.or(self.max_tokens).unwrap_or(1024);
 
-        let body = json!({
+        let mut body = json!({
             "model": self.model_id,
             "messages": [
                 {
@@ -59,39 +52,46 @@ impl LlmProvider for OpenAiProvider {
                 }
             ],
             "max_tokens": max_tokens,
-            "stream": false
+            "stream": stream
         });
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::Llm(format!("请求发送失败: {}", e)))?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp
-                .text()
-                .await
-                .unwrap_or_else(|_| "无法读取响应体".into());
-            return Err(AppError::Llm(format!(
-                "API 返回错误 ({}): {}",
-                status, text
-            )));
+        if stream {
+            // 要求服务端在最后一个 chunk 中附带 usage
+            body["stream_options"] = json!({ "include_usage": true });
         }
+        body
+    }
+
+    async fn send(&self, body: &serde_json::Value) -> Result<Response, AppError> {
+        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
+
+        send_with_retry(&self.retry, || {
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(body)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, AppError> {
+        let resp = self.send(&self.request_body(request, false)).await?;
 
         let json: serde_json::Value = resp
             .json()
             .await
-            .map_err(|e| AppError::Llm(format!("响应解析失败: {}", e)))?;
+            .map_err(|e| AppError::InvalidResponse(format!("响应解析失败: {}", e)))?;
 
         let commit_message = json["choices"][0]["message"]["content"]
             .as_str()
-            .ok_or_else(|| AppError::Llm("无法从响应中提取 commit message".into()))?
+            .ok_or_else(|| AppError::InvalidResponse("无法从响应中提取 commit message".into()))?
             .trim()
             .to_string();
 
@@ -102,4 +102,52 @@ impl LlmProvider for OpenAiProvider {
             usage_tokens,
         })
     }
+
+    async fn generate_stream(
+        &self,
+        request: &LlmRequest,
+        on_delta: &mut DeltaHandler<'_>,
+    ) -> Result<LlmResponse, AppError> {
+        if !self.stream {
+            return self.generate(request).await;
+        }
+
+        let mut resp = self.send(&self.request_body(request, true)).await?;
+
+        let mut decoder = SseDecoder::new();
+        let mut content = String::new();
+        let mut usage_tokens = None;
+        'read: while let Some(chunk) = resp.chunk().await.map_err(|e| transport_error(&e))? {
+            for data in decoder.push(&chunk) {
+                if data == "[DONE]" {
+                    break 'read;
+                }
+                let json: serde_json::Value = serde_json::from_str(&data)
+                    .map_err(|e| AppError::InvalidResponse(format!("响应解析失败: {}", e)))?;
+
+                if let Some(delta) = json["choices"][0]["delta"]["content"].as_str()
+                    && !delta.is_empty()
+                {
+                    on_delta(delta);
+                    content.push_str(delta);
+                }
+                // usage 只出现在最后一个 chunk（choices 为空）
+                if let Some(n) = json["usage"]["total_tokens"].as_u64() {
+                    usage_tokens = Some(n as usize);
+                }
+            }
+        }
+
+        let commit_message = content.trim().to_string();
+        if commit_message.is_empty() {
+            return Err(AppError::InvalidResponse(
+                "无法从响应中提取 commit message".into(),
+            ));
+        }
+
+        Ok(LlmResponse {
+            commit_message,
+            usage_tokens,
+        })
+    }
 }
@@ -0,0 +1,110 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{Duration, SystemTime};
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use super::api_error::{api_error, transport_error};
+use crate::config::RetryConfig;
+use crate::error::AppError;
+
+/// 按重试策略发送 HTTP 请求，返回状态码为成功的响应。
+///
+/// 连接失败、超时以及 429/502/503/504 会按指数退避重试，并优先遵循
+/// 服务端返回的 `Retry-After`（超过 `max_delay_ms` 时不重试，直接返回错误）；
+/// 认证失败等其他 4xx 错误立即返回。
+/// `make_request` 每次尝试都会被调用一次，用于重新构建请求。
+pub async fn send_with_retry<F>(
+    policy: &RetryConfig,
+    mut make_request: F,
+) -> Result<Response, AppError>
+where
+    F: FnMut() -> RequestBuilder,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        let last_attempt = attempt >= max_attempts;
+        let delay = match make_request().send().await {
+            Ok(resp) if resp.status().is_success() => return Ok(resp),
+            Ok(resp) => {
+                let status = resp.status();
+                let retry_after = retry_after(&resp);
+                // 服务端要求的等待超过上限时不再重试，交给回退链改用下一个模型
+                let wait_too_long =
+                    retry_after.is_some_and(|d| d > Duration::from_millis(policy.max_delay_ms));
+                if last_attempt || !is_retryable_status(status) || wait_too_long {
+                    let text = resp
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "无法读取响应体".into());
+                    return Err(api_error(status, retry_after, &text));
+                }
+                let delay = retry_after.unwrap_or_else(|| backoff(policy, attempt));
+                eprintln!(
+                    "[警告] API 返回 {}，{:.1} 秒后进行第 {} 次重试",
+                    status,
+                    delay.as_secs_f64(),
+                    attempt
+                );
+                delay
+            }
+            Err(e) => {
+                if last_attempt || !(e.is_connect() || e.is_timeout() || e.is_request()) {
+                    return Err(transport_error(&e));
+                }
+                let delay = backoff(policy, attempt);
+                eprintln!(
+                    "[警告] 请求发送失败: {}，{:.1} 秒后进行第 {} 次重试",
+                    e,
+                    delay.as_secs_f64(),
+                    attempt
+                );
+                delay
+            }
+        };
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// 限流和网关类错误是暂时性的，值得重试
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// 解析 `Retry-After` 头，支持秒数和 HTTP 日期两种格式
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let value = resp
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// 第 n 次重试的等待时间：base * 2^(n-1)，可选叠加 [0, base) 的随机抖动
+fn backoff(policy: &RetryConfig, attempt: u32) -> Duration {
+    let exp = policy
+        .base_delay_ms
+        .saturating_mul(1u64 << (attempt - 1).min(16));
+    let jitter = if policy.jitter && policy.base_delay_ms > 0 {
+        // RandomState 每次构造都带有随机种子，足以作为抖动来源
+        RandomState::new().hash_one(attempt) % policy.base_delay_ms
+    } else {
+        0
+    };
+    Duration::from_millis(exp.saturating_add(jitter).min(policy.max_delay_ms))
+}
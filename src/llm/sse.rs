@@ -0,0 +1,29 @@
+/// Server-Sent Events 增量解码器。
+/// 网络分块可能在任意字节处截断（包括 UTF-8 多字节字符中间），
+/// 因此按字节缓冲，只在遇到完整行时才解码。
+#[derive(Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一段字节，返回其中所有完整的 `data:` 负载
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if let Some(data) = line.strip_prefix("data:") {
+                events.push(data.trim_start().to_string());
+            }
+        }
+        events
+    }
+}
@@ -4,6 +4,9 @@ use super::request::LlmRequest;
 use super::response::LlmResponse;
 use crate::error::AppError;
 
+/// 流式输出的增量回调，每收到一段文本调用一次。
+pub type DeltaHandler<'a> = dyn FnMut(&str) + Send + 'a;
+
 /// LLM 后端的统一抽象。
 /// 新增模型后端只需实现此 trait（策略模式）。
 #[async_trait]
@@ -13,4 +16,19 @@ pub trait LlmProvider: Send + Sync {
 
     /// 发送请求并获取生成的 commit message
     async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, AppError>;
+
+    /// 以流式方式生成 commit message，每收到一段增量文本就调用 `on_delta`，
+    /// 结束后返回完整的响应。不支持流式的后端回退到阻塞的 `generate`。
+    async fn generate_stream(
+        &self,
+        request: &LlmRequest,
+        _on_delta: &mut DeltaHandler<'_>,
+    ) -> Result<LlmResponse, AppError> {
+        self.generate(request).await
+    }
+
+    /// 列出后端实际安装的模型，不支持查询的后端返回空列表
+    async fn installed_models(&self) -> Result<Vec<String>, AppError> {
+        Ok(Vec::new())
+    }
 }
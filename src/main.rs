@@ -1,24 +1,32 @@
+use std::path::Path;
+
 use clap::Parser;
 
-use llmc::app::App;
-use llmc::cli::CliArgs;
+use llmc::app::{App, NamedProvider, RunOptions};
+use llmc::cli::{CliArgs, Command, HookAction};
 use llmc::config::AppConfig;
 use llmc::error::AppError;
-use llmc::git::GitRepository;
+use llmc::git::{GitOperations, GitRepository};
 use llmc::llm::LlmProviderFactory;
-use llmc::prompt::TerminalPrompt;
+use llmc::prompt::{TerminalPrompt, UserInteraction};
+use llmc::tokenizer::TokenizerFactory;
 
 #[tokio::main]
 async fn main() {
     if let Err(e) = run().await {
         eprintln!("错误: {}", e);
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
 
 async fn run() -> Result<(), AppError> {
     let args = CliArgs::parse();
 
+    // 安装和删除钩子不需要配置文件和模型
+    if let Some(Command::Hook { action }) = &args.command {
+        return manage_hook(action, args.config.as_deref());
+    }
+
     // 解析配置文件路径（--config > ~/.config/llmc/ > ./config.toml > 自动创建）
     let config_path = AppConfig::resolve_config_path(args.config.as_deref())?;
     let mut config = AppConfig::load(&config_path)?;
@@ -35,26 +43,93 @@ async fn run() -> Result<(), AppError> {
         return Ok(());
     }
 
-    // 确定使用的模型
-    let model_name = args.model.as_deref().unwrap_or(&config.default_model);
+    // 确定使用的模型回退链，并为每个模型创建 provider
+    let ui = TerminalPrompt::new();
+    let llms = create_providers(&config, args.model.as_deref(), &ui)?;
+    let tokenizer = TokenizerFactory::create(config.find_model(&llms[0].0)?)?;
+    let git = GitRepository::new(config.diff.clone());
+    let app = App::new(config, Box::new(git), llms, tokenizer, Box::new(ui));
 
     // 如果是 --model_list，展示后退出
     if args.model_list {
-        let ui = TerminalPrompt::new();
-        let git = GitRepository::new();
-        let model_config = config.find_model(model_name)?;
-        let llm = LlmProviderFactory::create(model_config)?;
-        let app = App::new(config, Box::new(git), llm, Box::new(ui));
-        app.list_models();
-        return Ok(());
+        return app.list_models().await;
     }
 
-    // 正常流程：查找模型配置 → 创建 provider → 运行
-    let model_config = config.find_model(model_name)?;
-    let llm = LlmProviderFactory::create(model_config)?;
-    let git = GitRepository::new();
-    let ui = TerminalPrompt::new();
+    if let Some(Command::Reword { range }) = &args.command {
+        return app.reword(range).await;
+    }
+
+    // 正常流程
+    let options = RunOptions {
+        all: args.all,
+        include_untracked: args.include_untracked,
+        preview: args.preview,
+        amend: args.amend,
+        no_verify: args.no_verify,
+        message_file: args.message_file,
+    };
+    app.run(&options).await
+}
 
-    let app = App::new(config, Box::new(git), llm, Box::new(ui));
-    app.run().await
+/// 在当前仓库安装或删除 prepare-commit-msg 钩子
+fn manage_hook(action: &HookAction, config: Option<&Path>) -> Result<(), AppError> {
+    let git = GitRepository::default();
+    let repo_path = git.discover_repo(&std::env::current_dir()?)?;
+    match action {
+        HookAction::Install { force } => {
+            let path = git.install_hook(&repo_path, &hook_command(config)?, *force)?;
+            println!("已安装钩子: {}", path.display());
+        }
+        HookAction::Uninstall => {
+            let path = git.uninstall_hook(&repo_path)?;
+            println!("已删除钩子: {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// 钩子中调用 llmc 的命令。使用当前可执行文件的绝对路径，
+/// IDE 等环境的 PATH 中可能没有 llmc；安装时指定了 --config 则一并写入
+fn hook_command(config: Option<&Path>) -> Result<String, AppError> {
+    let exe = std::env::current_exe()?;
+    let mut command = shell_quote(&exe.to_string_lossy());
+    if let Some(config) = config {
+        let config = std::path::absolute(config)?;
+        command.push_str(&format!(
+            " --config {}",
+            shell_quote(&config.to_string_lossy())
+        ));
+    }
+    Ok(command)
+}
+
+/// 用单引号包裹，供 sh 脚本使用
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 按回退链创建 provider。创建失败（如缺少 API Key）的模型会被跳过，
+/// 只有全部失败时才返回错误。
+fn create_providers(
+    config: &AppConfig,
+    selected: Option<&str>,
+    ui: &dyn UserInteraction,
+) -> Result<Vec<NamedProvider>, AppError> {
+    let chain = config.model_chain(selected)?;
+    let mut llms = Vec::new();
+    let mut failures = Vec::new();
+    for model_config in chain {
+        match LlmProviderFactory::create(model_config) {
+            Ok(llm) => llms.push((model_config.name.clone(), llm)),
+            Err(e) => failures.push((&model_config.name, e)),
+        }
+    }
+
+    if llms.is_empty() && !failures.is_empty() {
+        return Err(failures.swap_remove(0).1);
+    }
+    for (name, e) in failures {
+        ui.warn(&format!("跳过模型 {}: {}", name, e));
+    }
+    Ok(llms)
 }
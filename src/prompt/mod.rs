@@ -2,4 +2,4 @@ mod terminal;
 mod traits;
 
 pub use terminal::TerminalPrompt;
-pub use traits::UserInteraction;
+pub use traits::{RewordChoice, UserInteraction};
@@ -1,6 +1,11 @@
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::process::Command;
 
-use super::UserInteraction;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::{RewordChoice, UserInteraction};
 use crate::config::ModelConfig;
 use crate::error::AppError;
 
@@ -8,13 +13,100 @@ use crate::error::AppError;
 #[derive(Default)]
 pub struct TerminalPrompt;
 
+/// 并排展示时每一栏的显示宽度
+const COLUMN_WIDTH: usize = 48;
+
 impl TerminalPrompt {
     pub fn new() -> Self {
         Self
     }
+
+    fn read_line(&self) -> Result<String, AppError> {
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        Ok(input.trim().to_lowercase())
+    }
+
+    /// 按 git 的顺序（$GIT_EDITOR、core.editor、$VISUAL、$EDITOR）选择编辑器编辑 message，
+    /// 去掉以 # 开头的注释行
+    fn edit_message(&self, initial: &str) -> Result<String, AppError> {
+        let from_env = |key: &str| env::var(key).ok().filter(|v| !v.trim().is_empty());
+        let editor = from_env("GIT_EDITOR")
+            .or_else(|| {
+                git2::Config::open_default()
+                    .and_then(|c| c.get_string("core.editor"))
+                    .ok()
+            })
+            .or_else(|| from_env("VISUAL"))
+            .or_else(|| from_env("EDITOR"))
+            .unwrap_or_else(|| "vi".to_string());
+        // 随机名称、仅当前用户可读写的临时文件（O_EXCL 创建），返回时自动删除
+        let mut file = tempfile::Builder::new()
+            .prefix("llmc-")
+            .suffix("-EDITMSG")
+            .tempfile()?;
+        write!(
+            file,
+            "{}\n\n# 编辑 commit message，以 # 开头的行会被忽略，清空则跳过该提交\n",
+            initial.trim_end()
+        )?;
+        let path = file.path().to_path_buf();
+
+        // 通过 shell 启动，支持 "code --wait" 这类带参数的编辑器配置
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg(&editor)
+            .arg(&path)
+            .status();
+        let content = fs::read_to_string(&path);
+        drop(file);
+        if !status?.success() {
+            return Err(AppError::Io(io::Error::other(format!(
+                "编辑器 {} 异常退出",
+                editor
+            ))));
+        }
+
+        let content = content?;
+        let message: Vec<&str> = content
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        Ok(message.join("\n").trim().to_string())
+    }
+}
+
+/// 按显示宽度截断并补齐到 width，过长时以 … 结尾
+fn pad(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    if text.width() > width {
+        for c in text.chars() {
+            let w = c.width().unwrap_or(0);
+            if used + w + 1 > width {
+                break;
+            }
+            out.push(c);
+            used += w;
+        }
+        out.push('…');
+        used += 1;
+    } else {
+        out.push_str(text);
+        used = text.width();
+    }
+    out.push_str(&" ".repeat(width.saturating_sub(used)));
+    out
 }
 
 impl UserInteraction for TerminalPrompt {
+    fn display_stream_delta(&self, delta: &str) {
+        print!("{}", delta);
+        let _ = io::stdout().flush();
+    }
+
     fn confirm_commit(&self, message: &str) -> Result<bool, AppError> {
         println!("\n===== 生成的 Commit Message =====");
         println!("{}", message);
@@ -28,6 +120,73 @@ impl UserInteraction for TerminalPrompt {
         Ok(answer == "y" || answer == "yes")
     }
 
+    fn confirm_breaking_change(&self, changes: &[String]) -> Result<bool, AppError> {
+        println!("\n检测到可能破坏兼容性的公开 API 变化:");
+        for change in changes {
+            println!("  - {}", change);
+        }
+        print!("是否在 commit message 中添加 BREAKING CHANGE 脚注？(y/n): ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let answer = input.trim().to_lowercase();
+        Ok(answer == "y" || answer == "yes")
+    }
+
+    fn review_reword(
+        &self,
+        commit: &str,
+        position: (usize, usize),
+        original: &str,
+        proposed: &str,
+    ) -> Result<RewordChoice, AppError> {
+        println!("\n[{}/{}] {}", position.0, position.1, commit);
+        println!("{} │ 新 message", pad("原 message", COLUMN_WIDTH));
+        println!(
+            "{}┼{}",
+            "─".repeat(COLUMN_WIDTH + 1),
+            "─".repeat(COLUMN_WIDTH + 1)
+        );
+        let old_lines: Vec<&str> = original.trim_end().lines().collect();
+        let new_lines: Vec<&str> = proposed.trim_end().lines().collect();
+        for i in 0..old_lines.len().max(new_lines.len()) {
+            let old = old_lines.get(i).copied().unwrap_or_default();
+            let new = new_lines.get(i).copied().unwrap_or_default();
+            println!("{} │ {}", pad(old, COLUMN_WIDTH), new);
+        }
+
+        loop {
+            print!("\n接受(a) / 编辑(e) / 跳过(s): ");
+            match self.read_line()?.as_str() {
+                "a" | "accept" | "y" | "yes" => return Ok(RewordChoice::Accept),
+                "s" | "skip" | "n" | "no" => return Ok(RewordChoice::Skip),
+                "e" | "edit" => {
+                    let edited = self.edit_message(proposed)?;
+                    if edited.is_empty() {
+                        return Ok(RewordChoice::Skip);
+                    }
+                    return Ok(RewordChoice::Edit(edited));
+                }
+                _ => println!("请输入 a、e 或 s"),
+            }
+        }
+    }
+
+    fn confirm_reword(&self, count: usize) -> Result<bool, AppError> {
+        print!("\n将重写 {} 个提交的 message，是否继续？(y/n): ", count);
+        let answer = self.read_line()?;
+        Ok(answer == "y" || answer == "yes")
+    }
+
+    fn display_progress(&self, label: &str, done: usize, total: usize) {
+        print!("\r{}: {}/{}", label, done, total);
+        if done == total {
+            println!();
+        }
+        let _ = io::stdout().flush();
+    }
+
     fn warn(&self, message: &str) {
         eprintln!("[警告] {}", message);
     }
@@ -48,4 +207,13 @@ impl UserInteraction for TerminalPrompt {
         }
         println!();
     }
+
+    fn display_installed_models(&self, provider: &str, models: &[String]) {
+        println!("{} 已安装的模型:", provider);
+        println!("{}", "-".repeat(70));
+        for m in models {
+            println!("{m}");
+        }
+        println!();
+    }
 }
@@ -1,15 +1,50 @@
 use crate::config::ModelConfig;
 use crate::error::AppError;
 
+/// 用户对一条改写建议的处理结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewordChoice {
+    /// 使用生成的 message
+    Accept,
+    /// 使用用户编辑后的 message
+    Edit(String),
+    /// 保留原 message
+    Skip,
+}
+
 /// 用户交互的抽象接口。
 /// 将 stdin/stdout 交互解耦，便于测试和未来替换为 TUI。
-pub trait UserInteraction {
+pub trait UserInteraction: Send + Sync {
+    /// 实时展示 LLM 流式输出的增量文本
+    fn display_stream_delta(&self, delta: &str);
+
     /// 展示 commit message 并请求用户确认
     fn confirm_commit(&self, message: &str) -> Result<bool, AppError>;
 
+    /// 展示检测到的破坏性变更，询问是否在提交信息中添加 `BREAKING CHANGE:` 脚注
+    fn confirm_breaking_change(&self, changes: &[String]) -> Result<bool, AppError>;
+
+    /// 并排展示提交的原 message 与生成的 message，由用户选择接受、编辑或跳过
+    fn review_reword(
+        &self,
+        commit: &str,
+        position: (usize, usize),
+        original: &str,
+        proposed: &str,
+    ) -> Result<RewordChoice, AppError>;
+
+    /// 确认是否重写指定数量的提交
+    fn confirm_reword(&self, count: usize) -> Result<bool, AppError>;
+
+    /// 展示多步骤任务的进度
+    fn display_progress(&self, label: &str, done: usize, total: usize);
+
     /// 展示警告信息
     fn warn(&self, message: &str);
 
     /// 展示模型列表
     fn display_model_list(&self, models: &[ModelConfig]);
+
+    /// 展示后端实际安装的模型
+    fn display_installed_models(&self, provider: &str, models: &[String]);
 }
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use super::Tokenizer;
+use crate::error::AppError;
+
+/// 基于字节级 BPE 的分词器，兼容 tiktoken 格式的 cl100k/o200k 词表。
+///
+/// 词表文件每行为 `<base64 编码的 token> <rank>`，rank 越小合并优先级越高。
+/// 预切分规则按 cl100k 的正则手工实现，对 o200k 词表同样能给出足够准确的计数。
+pub struct BpeTokenizer {
+    name: String,
+    ranks: HashMap<Vec<u8>, u32>,
+}
+
+impl BpeTokenizer {
+    /// 从本地 tiktoken 词表文件加载
+    pub fn from_file(path: &Path) -> Result<Self, AppError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("无法读取词表文件 {}: {}", path.display(), e)))?;
+
+        let mut ranks = HashMap::new();
+        for (lineno, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = line.split_once(' ').and_then(|(token, rank)| {
+                Some((
+                    STANDARD.decode(token).ok()?,
+                    rank.trim().parse::<u32>().ok()?,
+                ))
+            });
+            let (token, rank) = parsed.ok_or_else(|| {
+                AppError::Config(format!(
+                    "词表文件 {} 第 {} 行格式错误",
+                    path.display(),
+                    lineno + 1
+                ))
+            })?;
+            ranks.insert(token, rank);
+        }
+
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "bpe".into());
+        Ok(Self { name, ranks })
+    }
+
+    /// 对单个预切分片段执行 BPE 合并，返回 token 数
+    fn count_piece(&self, piece: &[u8]) -> usize {
+        if piece.len() <= 1 || self.ranks.contains_key(piece) {
+            return 1;
+        }
+
+        // boundaries[i]..boundaries[i + 1] 是当前的第 i 个 token
+        let mut boundaries: Vec<usize> = (0..=piece.len()).collect();
+        loop {
+            let best = (0..boundaries.len().saturating_sub(2))
+                .filter_map(|i| {
+                    let pair = &piece[boundaries[i]..boundaries[i + 2]];
+                    self.ranks.get(pair).map(|&rank| (rank, i))
+                })
+                .min();
+            match best {
+                Some((_, i)) => {
+                    boundaries.remove(i + 1);
+                }
+                None => break,
+            }
+        }
+        boundaries.len() - 1
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        pretokenize(text)
+            .map(|piece| self.count_piece(piece.as_bytes()))
+            .sum()
+    }
+}
+
+/// 按 cl100k 的预切分正则将文本切成片段：
+/// `'(?i:[sdmt]|ll|ve|re)|[^\r\n\p{L}\p{N}]?+\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]++[\r\n]*|\s*[\r\n]|\s+(?!\S)|\s+`
+fn pretokenize(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let len = next_piece_len(rest);
+        let (piece, tail) = rest.split_at(len);
+        rest = tail;
+        Some(piece)
+    })
+}
+
+/// 返回 text 开头第一个片段的字节长度（至少为一个字符）
+fn next_piece_len(text: &str) -> usize {
+    let chars: Vec<(usize, char)> = text.char_indices().take(64).collect();
+    // 只看前 64 个字符，超出部分留给下一个片段
+    let window_end = chars.last().map_or(0, |&(pos, c)| pos + c.len_utf8());
+    let byte_at = |i: usize| chars.get(i).map_or(window_end, |&(pos, _)| pos);
+    let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let is_newline = |c: char| c == '\r' || c == '\n';
+    let is_other = |c: char| !c.is_whitespace() && !c.is_alphabetic() && !c.is_numeric();
+    let run = |start: usize, pred: &dyn Fn(char) -> bool| {
+        (start..).take_while(|&i| at(i).is_some_and(pred)).count()
+    };
+
+    let first = match at(0) {
+        Some(c) => c,
+        None => return 0,
+    };
+
+    // 1. 英文缩写：'s 't 're 've 'm 'll 'd
+    if first == '\'' {
+        let lower: String = text[byte_at(1)..byte_at(3)].to_lowercase();
+        if lower.starts_with("ll") || lower.starts_with("ve") || lower.starts_with("re") {
+            return byte_at(3);
+        }
+        if lower.starts_with(['s', 'd', 'm', 't']) {
+            return byte_at(2);
+        }
+    }
+
+    // 2. 可选的一个非字母数字前缀 + 字母串
+    let prefix = usize::from(!first.is_alphabetic() && !first.is_numeric() && !is_newline(first));
+    let letters = run(prefix, &|c: char| c.is_alphabetic());
+    if letters > 0 {
+        return byte_at(prefix + letters);
+    }
+
+    // 3. 最多三位数字
+    if first.is_numeric() {
+        return byte_at(run(0, &|c: char| c.is_numeric()).min(3));
+    }
+
+    // 4. 可选空格 + 标点串 + 紧随的换行
+    let space = usize::from(first == ' ');
+    let punct = run(space, &is_other);
+    if punct > 0 {
+        let end = space + punct;
+        return byte_at(end + run(end, &is_newline));
+    }
+
+    // 5-7. 空白：优先带换行的空白，其次把最后一个空白留给后面的单词
+    let ws = run(0, &|c: char| c.is_whitespace());
+    if let Some(last_nl) = (0..ws).rev().find(|&i| at(i).is_some_and(is_newline)) {
+        return byte_at(last_nl + 1);
+    }
+    if ws > 1 && at(ws).is_some() {
+        return byte_at(ws - 1);
+    }
+    byte_at(ws.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(text: &str) -> Vec<&str> {
+        pretokenize(text).collect()
+    }
+
+    fn tokenizer(vocab: &[(&str, u32)]) -> BpeTokenizer {
+        BpeTokenizer {
+            name: "test".into(),
+            ranks: vocab
+                .iter()
+                .map(|&(token, rank)| (token.as_bytes().to_vec(), rank))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn splits_contractions() {
+        assert_eq!(pieces("I'm don't"), ["I", "'m", " don", "'t"]);
+        assert_eq!(pieces("we'LL they've"), ["we", "'LL", " they", "'ve"]);
+    }
+
+    #[test]
+    fn splits_digit_runs_into_groups_of_three() {
+        assert_eq!(pieces("12345"), ["123", "45"]);
+        assert_eq!(pieces("v2 1000"), ["v", "2", " ", "100", "0"]);
+    }
+
+    #[test]
+    fn attaches_single_space_to_the_following_word() {
+        assert_eq!(pieces("hello world"), ["hello", " world"]);
+        assert_eq!(pieces("a   b"), ["a", "  ", " b"]);
+        assert_eq!(pieces("x\n\ny"), ["x", "\n\n", "y"]);
+        assert_eq!(pieces("foo(bar);\n"), ["foo", "(bar", ");\n"]);
+    }
+
+    #[test]
+    fn keeps_cjk_runs_together() {
+        assert_eq!(pieces("你好世界"), ["你好世界"]);
+        assert_eq!(pieces("中文，测试"), ["中文", "，测试"]);
+    }
+
+    #[test]
+    fn merges_pairs_by_rank() {
+        let bpe = tokenizer(&[("ab", 0), ("bc", 1), ("abc", 2)]);
+        assert_eq!(bpe.count_piece(b"abc"), 1);
+        assert_eq!(bpe.count_piece(b"abcd"), 2);
+        assert_eq!(bpe.count_piece(b"bcab"), 2);
+        assert_eq!(bpe.count_piece(b"xyz"), 3);
+        assert_eq!(bpe.count_tokens("abcd abc"), 4);
+    }
+}
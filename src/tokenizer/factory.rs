@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+use super::{BpeTokenizer, HeuristicTokenizer, Tokenizer};
+use crate::config::ModelConfig;
+use crate::error::AppError;
+
+/// 根据 ModelConfig.tokenizer 字段创建对应的 Tokenizer 实现。
+pub struct TokenizerFactory;
+
+impl TokenizerFactory {
+    pub fn create(model_config: &ModelConfig) -> Result<Box<dyn Tokenizer>, AppError> {
+        match model_config.tokenizer.as_deref() {
+            None | Some("heuristic") => Ok(Box::new(HeuristicTokenizer::new())),
+            Some(path) => Ok(Box::new(BpeTokenizer::from_file(&expand_home(path))?)),
+        }
+    }
+}
+
+/// 展开路径开头的 `~/`
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
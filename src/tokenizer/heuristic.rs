@@ -0,0 +1,30 @@
+use super::Tokenizer;
+
+/// 不依赖词表的启发式分词器，未配置词表时使用。
+/// ASCII 文本按 4 字节 1 token 估算；CJK 等非 ASCII 字符在主流 BPE 词表中
+/// 通常各占 1 个以上 token，按每字符 1 token 估算，避免按字节数严重高估。
+#[derive(Default)]
+pub struct HeuristicTokenizer;
+
+impl HeuristicTokenizer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Tokenizer for HeuristicTokenizer {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        let (ascii, other) = text.chars().fold((0usize, 0usize), |(ascii, other), c| {
+            if c.is_ascii() {
+                (ascii + 1, other)
+            } else {
+                (ascii, other + 1)
+            }
+        });
+        ascii.div_ceil(4) + other
+    }
+}
@@ -0,0 +1,9 @@
+mod bpe;
+mod factory;
+mod heuristic;
+mod traits;
+
+pub use bpe::BpeTokenizer;
+pub use factory::TokenizerFactory;
+pub use heuristic::HeuristicTokenizer;
+pub use traits::Tokenizer;
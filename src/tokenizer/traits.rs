@@ -0,0 +1,9 @@
+/// 分词器的统一抽象，用于在发送前估算 prompt 的 token 数。
+/// 新增分词算法只需实现此 trait。
+pub trait Tokenizer: Send + Sync {
+    /// 返回该分词器的名称标识
+    fn name(&self) -> &str;
+
+    /// 计算文本的 token 数
+    fn count_tokens(&self, text: &str) -> usize;
+}